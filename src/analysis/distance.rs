@@ -0,0 +1,31 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use mazesolver::neighbours;
+
+type Node = (u32, u32);
+
+pub fn distances(start: Node, maze: &HashSet<Node>) -> HashMap<Node, u32> {
+    // Distance of each node from the start along the passages
+    let mut distance: HashMap<Node, u32> = HashMap::new();
+    let mut queue: VecDeque<Node> = VecDeque::new();
+
+    if maze.contains(&start) {
+        distance.insert(start, 0);
+        queue.push_back(start);
+    }
+
+    // Flood out from the start, every new node is one further away than the
+    // node it was reached from.
+    while let Some(current) = queue.pop_front() {
+        let next_distance = distance[&current] + 1;
+
+        for neighbour in neighbours(current, maze) {
+            if !distance.contains_key(neighbour) {
+                distance.insert(*neighbour, next_distance);
+                queue.push_back(*neighbour);
+            }
+        }
+    }
+
+    distance
+}
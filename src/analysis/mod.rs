@@ -0,0 +1,243 @@
+mod distance;
+mod solutions;
+mod stats;
+mod validate;
+
+pub use self::stats::MazeStats;
+pub use self::validate::Validation;
+
+use std::collections::{HashMap, HashSet};
+
+/// Counts how many different shortest paths lead from the start to the end.
+pub fn count_shortest_paths(start: (u32, u32), end: (u32, u32), maze: &HashSet<(u32, u32)>) -> u64 {
+    solutions::count_shortest_paths(start, end, maze)
+}
+
+/// Finds up to `limit` paths from the start to the end that never visit the
+/// same node twice. Each path runs from the end back to the start.
+pub fn find_solutions(
+    start: (u32, u32),
+    end: (u32, u32),
+    maze: &HashSet<(u32, u32)>,
+    limit: usize,
+) -> Vec<Vec<&(u32, u32)>> {
+    solutions::simple_paths(start, end, maze, limit)
+}
+
+/// Finds how far every reachable node is from the start, following the passages.
+pub fn distances_from(start: (u32, u32), maze: &HashSet<(u32, u32)>) -> HashMap<(u32, u32), u32> {
+    distance::distances(start, maze)
+}
+
+/// Checks a maze over: whether its border is closed, every cell is there and
+/// can be reached from the start, and where any loops are.
+pub fn validate(
+    start: (u32, u32),
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+) -> Validation {
+    validate::validate(start, height, width, maze)
+}
+
+/// Measures the maze's dead ends, corridors and the shortest way through it,
+/// and scores how hard it is to solve.
+pub fn stats(start: (u32, u32), end: (u32, u32), maze: &HashSet<(u32, u32)>) -> MazeStats {
+    stats::stats(start, end, maze)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mazebuilder::{generate_maze, generate_maze_seeded, Generator};
+    use testing::maze_from_rows;
+
+    #[test]
+    fn test_perfect_maze_single_solution() {
+        let maze = generate_maze(
+            Generator::Kruskal {
+                height: 31,
+                width: 31,
+            },
+            false,
+        );
+
+        assert_eq!(count_shortest_paths((1, 1), (29, 29), &maze), 1);
+        assert_eq!(find_solutions((1, 1), (29, 29), &maze, 5).len(), 1);
+    }
+
+    #[test]
+    fn test_ladder_solutions() {
+        let maze = maze_from_rows(&["#######", "#.....#", "#.#.#.#", "#.....#", "#######"]);
+
+        assert_eq!(count_shortest_paths((1, 1), (5, 3), &maze), 3);
+
+        let solutions = find_solutions((1, 1), (5, 3), &maze, 10);
+        assert_eq!(solutions.len(), 4);
+        assert!(solutions
+            .iter()
+            .all(|path| *path[0] == (5, 3) && **path.last().unwrap() == (1, 1)));
+
+        assert_eq!(find_solutions((1, 1), (5, 3), &maze, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_unreachable_end() {
+        let maze = maze_from_rows(&["#######", "#...#.#", "#.#.#.#", "#...#.#", "#######"]);
+
+        assert_eq!(count_shortest_paths((1, 1), (5, 3), &maze), 0);
+        assert!(find_solutions((1, 1), (5, 3), &maze, 10).is_empty());
+    }
+
+    #[test]
+    fn test_start_in_wall() {
+        let maze = maze_from_rows(&["#######", "#.....#", "#.#.#.#", "#.....#", "#######"]);
+
+        // Like the solvers, a start that isn't a passage finds nothing
+        assert_eq!(count_shortest_paths((0, 0), (5, 3), &maze), 0);
+        assert!(find_solutions((2, 2), (5, 3), &maze, 10).is_empty());
+    }
+
+    #[test]
+    fn test_distances_from() {
+        let maze = maze_from_rows(&["#######", "#...#.#", "#.#.#.#", "#...#.#", "#######"]);
+
+        let distances = distances_from((1, 1), &maze);
+
+        assert_eq!(distances.len(), 8);
+        assert_eq!(distances[&(1, 1)], 0);
+        assert_eq!(distances[&(3, 3)], 4);
+        assert_eq!(distances[&(3, 1)], 2);
+        assert!(!distances.contains_key(&(5, 1)));
+    }
+
+    #[test]
+    fn test_validate_generated_mazes() {
+        let perfect = generate_maze(
+            Generator::DFS {
+                height: 21,
+                width: 31,
+            },
+            false,
+        );
+        let validation = validate((1, 1), 21, 31, &perfect);
+
+        assert!(validation.is_perfect());
+        assert_eq!(validation.passages, 2 * 10 * 15 - 1);
+        assert_eq!(validation.links, validation.passages - 1);
+    }
+
+    #[test]
+    fn test_validate_loops() {
+        let maze = maze_from_rows(&["#######", "#.....#", "#.#.#.#", "#.....#", "#######"]);
+
+        let validation = validate((1, 1), 5, 7, &maze);
+        assert!(validation.is_valid());
+        assert!(!validation.is_perfect());
+        assert_eq!(validation.cycles.len(), 2);
+
+        // Each loop goes all the way round one of the walls
+        for cycle in &validation.cycles {
+            assert_eq!(cycle.len(), 8);
+            for (index, node) in cycle.iter().enumerate() {
+                let next = cycle[(index + 1) % cycle.len()];
+                let distance =
+                    (node.0 as i64 - next.0 as i64).abs() + (node.1 as i64 - next.1 as i64).abs();
+                assert_eq!(distance, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_broken_mazes() {
+        let maze = maze_from_rows(&["#######", "#...#..", "#.###.#", "#.###.#", "#######"]);
+
+        let validation = validate((1, 1), 5, 7, &maze);
+        assert!(!validation.border_closed);
+        assert_eq!(validation.missing_cells, vec![(3, 3)]);
+        assert_eq!(validation.isolated_regions.len(), 1);
+        assert_eq!(validation.isolated_regions[0].len(), 4);
+        assert!(validation.cycles.is_empty());
+        assert!(!validation.is_connected());
+        assert!(!validation.is_valid());
+
+        // Nothing can be reached from a start inside a wall
+        let validation = validate((0, 0), 5, 7, &maze);
+        assert_eq!(validation.isolated_regions.len(), 2);
+    }
+
+    #[test]
+    fn test_stats_by_hand() {
+        let maze = maze_from_rows(&["#######", "#.....#", "#.###.#", "#.#...#", "#######"]);
+
+        let stats = stats((1, 1), (3, 3), &maze);
+
+        // One long corridor running round from one dead end to the other,
+        // with the start part way along it
+        assert_eq!(stats.passages, 11);
+        assert_eq!(stats.cells, 6);
+        assert_eq!(stats.dead_ends, 2);
+        assert_eq!(stats.junctions, 0);
+        assert_eq!(stats.corridors, 1);
+        assert_eq!(stats.average_corridor_length, 10.0);
+        assert_eq!(stats.max_dead_end_depth, 10);
+        assert_eq!(stats.solution_length, 8);
+        assert_eq!(stats.solution_ratio, 5.0 / 6.0);
+        assert_eq!(stats.decision_points, 1);
+        assert_eq!(stats.tortuosity, 2.0);
+        assert_eq!(stats.difficulty, 10.0);
+    }
+
+    #[test]
+    fn test_stats_branches() {
+        let maze = maze_from_rows(&["#######", "#.....#", "#.#.###", "#.#...#", "#######"]);
+
+        let stats = stats((1, 1), (5, 3), &maze);
+
+        assert_eq!(stats.dead_ends, 3);
+        assert_eq!(stats.junctions, 1);
+        assert_eq!(stats.corridors, 3);
+        assert_eq!(stats.average_dead_end_depth, (4.0 + 2.0 + 4.0) / 3.0);
+        assert_eq!(stats.solution_length, 6);
+        assert_eq!(stats.decision_points, 2);
+        assert_eq!(stats.dead_end_ratio, 3.0 / 6.0);
+    }
+
+    #[test]
+    fn test_stats_unreachable_end() {
+        let maze = maze_from_rows(&["#######", "#...#.#", "#.#.#.#", "#...#.#", "#######"]);
+
+        let stats = stats((1, 1), (5, 3), &maze);
+        assert_eq!(stats.solution_length, 0);
+        assert_eq!(stats.difficulty, 0.0);
+    }
+
+    #[test]
+    fn test_stats_generators_differ() {
+        let dfs = generate_maze_seeded(
+            Generator::DFS {
+                height: 51,
+                width: 51,
+            },
+            0.0,
+            3,
+        );
+        let kruskal = generate_maze_seeded(
+            Generator::Kruskal {
+                height: 51,
+                width: 51,
+            },
+            0.0,
+            3,
+        );
+
+        let dfs = stats((1, 1), (49, 49), &dfs);
+        let kruskal = stats((1, 1), (49, 49), &kruskal);
+
+        // Depth first search winds along much longer corridors
+        assert_eq!(dfs.cells, 625);
+        assert!(dfs.river > kruskal.river);
+        assert!(dfs.dead_ends < kruskal.dead_ends);
+        assert!(dfs.average_corridor_length > kruskal.average_corridor_length);
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use mazesolver::{self, neighbours};
+
+type Node = (u32, u32);
+
+pub fn count_shortest_paths(start: Node, end: Node, maze: &HashSet<Node>) -> u64 {
+    // Distance of each node from the start
+    let mut distance: HashMap<&Node, u32> = HashMap::new();
+
+    // Number of shortest paths from the start to each node
+    let mut count: HashMap<&Node, u64> = HashMap::new();
+
+    let mut queue: VecDeque<&Node> = VecDeque::new();
+
+    // A start that isn't a passage has no paths out of it
+    let root = match maze.get(&start) {
+        Some(root) => root,
+        None => return 0,
+    };
+    distance.insert(root, 0);
+    count.insert(root, 1);
+    queue.push_back(root);
+
+    // Work through the maze a layer at a time, every node on the next layer
+    // can be reached by as many paths as all of its neighbours on this layer
+    // put together.
+    while let Some(current) = queue.pop_front() {
+        if *current == end {
+            return count[current];
+        }
+
+        let current_distance = distance[current];
+        let current_count = count[current];
+
+        for neighbour in neighbours(*current, maze) {
+            match distance.get(neighbour) {
+                None => {
+                    distance.insert(neighbour, current_distance + 1);
+                    count.insert(neighbour, current_count);
+                    queue.push_back(neighbour);
+                }
+                Some(&d) if d == current_distance + 1 => {
+                    let total = count[neighbour].saturating_add(current_count);
+                    count.insert(neighbour, total);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    // The end can't be reached
+    0
+}
+
+pub fn simple_paths(start: Node, end: Node, maze: &HashSet<Node>, limit: usize) -> Vec<Vec<&Node>> {
+    let mut paths = Vec::new();
+
+    if limit == 0 {
+        return paths;
+    }
+
+    // Anything filled in as a dead end can't be on a route to the end, so
+    // there is no need to walk down it.
+    let (_, filled, _) = mazesolver::solve_dead_end_filling(start, end, maze, false);
+
+    let root = match maze.get(&start) {
+        Some(root) => root,
+        None => return paths,
+    };
+    if *root == end {
+        paths.push(vec![root]);
+        return paths;
+    }
+
+    // The route walked so far, and the neighbours still to try from each
+    // node along it.
+    let mut path: Vec<&Node> = vec![root];
+    let mut on_path: HashSet<&Node> = HashSet::new();
+    on_path.insert(root);
+    let mut branches: Vec<Vec<&Node>> = vec![neighbours(*root, maze)];
+
+    while let Some(options) = branches.last_mut() {
+        let next = match options.pop() {
+            Some(node) => node,
+            None => {
+                // Nothing left to try here, step back
+                branches.pop();
+                if let Some(node) = path.pop() {
+                    on_path.remove(node);
+                }
+                continue;
+            }
+        };
+
+        if on_path.contains(next) || filled.contains(next) {
+            continue;
+        }
+
+        if *next == end {
+            // Paths are given from the end back to the start
+            let mut found = path.clone();
+            found.push(next);
+            found.reverse();
+            paths.push(found);
+
+            if paths.len() == limit {
+                break;
+            }
+            continue;
+        }
+
+        path.push(next);
+        on_path.insert(next);
+        branches.push(neighbours(*next, maze));
+    }
+
+    paths
+}
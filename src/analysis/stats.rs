@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+
+use super::distance::distances;
+use mazesolver::neighbours;
+
+type Node = (u32, u32);
+
+/// Measurements of a maze's shape, and how hard it is to find the way through.
+///
+/// Lengths are counted in steps between neighbouring passages, so going from
+/// one cell to the next is two steps.
+pub struct MazeStats {
+    /// Number of passage nodes in the maze
+    pub passages: usize,
+    /// Number of passages on the odd coordinates
+    pub cells: usize,
+    /// Passages with only one way out
+    pub dead_ends: usize,
+    /// Share of the cells that are dead ends
+    pub dead_end_ratio: f64,
+    /// Passages with three or more ways out
+    pub junctions: usize,
+    /// Runs of passage between dead ends and junctions
+    pub corridors: usize,
+    pub average_corridor_length: f64,
+    /// Share of the cells with exactly two ways out. Mazes with a high river
+    /// flow along long winding corridors, with few but deep dead ends.
+    pub river: f64,
+    /// Average distance from a dead end back to the junction it leads off
+    pub average_dead_end_depth: f64,
+    pub max_dead_end_depth: usize,
+    /// Length of the shortest way from the start to the end, zero if the end
+    /// can't be reached
+    pub solution_length: usize,
+    /// Share of the cells that the shortest way through passes, zero if the
+    /// end can't be reached
+    pub solution_ratio: f64,
+    /// Places along the shortest way through where there is more than one
+    /// way onwards
+    pub decision_points: usize,
+    /// How much longer the shortest way through is than a straight line
+    pub tortuosity: f64,
+    /// Decision points weighted by how far a wrong turn leads on average, so
+    /// mazes with many choices that each take a long time to rule out score
+    /// highest
+    pub difficulty: f64,
+}
+
+pub fn stats(start: Node, end: Node, maze: &HashSet<Node>) -> MazeStats {
+    let cells = maze
+        .iter()
+        .filter(|&&(x, y)| x % 2 == 1 && y % 2 == 1)
+        .count();
+
+    let mut dead_ends = 0;
+    let mut junctions = 0;
+    let mut straight_cells = 0;
+
+    for &node in maze {
+        match neighbours(node, maze).len() {
+            1 => dead_ends += 1,
+            2 if node.0 % 2 == 1 && node.1 % 2 == 1 => straight_cells += 1,
+            n if n >= 3 => junctions += 1,
+            _ => {}
+        }
+    }
+
+    // Follow every corridor out from each end of it, so each is walked twice
+    let mut walks = 0;
+    let mut walked = 0;
+    let mut depths = Vec::new();
+
+    for &node in maze {
+        let ways = neighbours(node, maze);
+        if ways.len() == 2 {
+            continue;
+        }
+
+        for &&next in &ways {
+            let length = corridor_length(node, next, maze);
+            walks += 1;
+            walked += length;
+
+            if ways.len() == 1 {
+                depths.push(length);
+            }
+        }
+    }
+
+    let (solution_length, decision_points) = solution(start, end, maze);
+    let solution_cells = if solution_length > 0 || start == end {
+        solution_length / 2 + 1
+    } else {
+        0
+    };
+
+    let average_dead_end_depth = average(depths.iter().sum(), depths.len());
+    let manhattan = (i64::from(start.0) - i64::from(end.0)).abs()
+        + (i64::from(start.1) - i64::from(end.1)).abs();
+
+    MazeStats {
+        passages: maze.len(),
+        cells,
+        dead_ends,
+        dead_end_ratio: average(dead_ends, cells),
+        junctions,
+        corridors: walks / 2,
+        average_corridor_length: average(walked, walks),
+        river: average(straight_cells, cells),
+        average_dead_end_depth,
+        max_dead_end_depth: depths.iter().cloned().max().unwrap_or(0),
+        solution_length,
+        solution_ratio: average(solution_cells, cells),
+        decision_points,
+        tortuosity: average(solution_length, manhattan as usize),
+        difficulty: decision_points as f64 * average_dead_end_depth,
+    }
+}
+
+// Number of steps from a dead end or junction, heading towards next, until
+// the next dead end or junction
+fn corridor_length(from: Node, next: Node, maze: &HashSet<Node>) -> usize {
+    let (mut previous, mut current) = (from, next);
+    let mut length = 1;
+
+    loop {
+        let ways = neighbours(current, maze);
+        if ways.len() != 2 || current == from {
+            return length;
+        }
+
+        let onward = if *ways[0] == previous {
+            *ways[1]
+        } else {
+            *ways[0]
+        };
+        previous = current;
+        current = onward;
+        length += 1;
+    }
+}
+
+// Walks a shortest way from the start to the end, counting the places a
+// choice has to be made on the way
+fn solution(start: Node, end: Node, maze: &HashSet<Node>) -> (usize, usize) {
+    let distance = distances(end, maze);
+
+    let mut current = start;
+    let length = match distance.get(&current) {
+        Some(&length) => length,
+        None => return (0, 0),
+    };
+
+    let mut decision_points = 0;
+
+    while current != end {
+        let ways = neighbours(current, maze);
+
+        // Every way out is a choice at the start, elsewhere one of them is
+        // back the way we came
+        let choices = if current == start {
+            ways.len()
+        } else {
+            ways.len() - 1
+        };
+        if choices > 1 {
+            decision_points += 1;
+        }
+
+        current = **ways
+            .iter()
+            .find(|way| distance.get(way) == Some(&(distance[&current] - 1)))
+            .expect("solution -- no step towards the end!");
+    }
+
+    (length as usize, decision_points)
+}
+
+fn average(total: usize, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use mazesolver::neighbours;
+
+type Node = (u32, u32);
+
+/// What was found when checking a maze over.
+pub struct Validation {
+    /// Number of passage nodes in the maze
+    pub passages: usize,
+    /// Number of pairs of passage nodes that sit next to each other
+    pub links: usize,
+    /// No passage runs along or past the outside edge of the maze
+    pub border_closed: bool,
+    /// Cells, on the odd coordinates inside the border, that are walls
+    pub missing_cells: Vec<Node>,
+    /// Groups of passages that can't be reached from the start
+    pub isolated_regions: Vec<Vec<Node>>,
+    /// Every loop in the maze, one for each link that could be walled up
+    /// without cutting anything off
+    pub cycles: Vec<Vec<Node>>,
+}
+
+impl Validation {
+    /// Every passage can be reached from the start.
+    pub fn is_connected(&self) -> bool {
+        self.isolated_regions.is_empty()
+    }
+
+    /// The maze is closed in, has all of its cells and they can all be
+    /// reached, though there may be more than one way between them.
+    pub fn is_valid(&self) -> bool {
+        self.border_closed && self.missing_cells.is_empty() && self.is_connected()
+    }
+
+    /// A valid maze with exactly one way between any two of its passages,
+    /// which makes it a spanning tree with one fewer link than passages.
+    pub fn is_perfect(&self) -> bool {
+        self.is_valid() && self.cycles.is_empty() && self.links + 1 == self.passages
+    }
+}
+
+pub fn validate(start: Node, height: u32, width: u32, maze: &HashSet<Node>) -> Validation {
+    let border_closed = maze
+        .iter()
+        .all(|&(x, y)| x > 0 && y > 0 && x + 1 < width && y + 1 < height);
+
+    let mut missing_cells = Vec::new();
+    for y in (1..height.saturating_sub(1)).step_by(2) {
+        for x in (1..width.saturating_sub(1)).step_by(2) {
+            if !maze.contains(&(x, y)) {
+                missing_cells.push((x, y));
+            }
+        }
+    }
+
+    // Walk a spanning tree over each connected region, starting with the one
+    // the start is in, so that every link left over closes a loop
+    let mut nodes: Vec<Node> = maze.iter().cloned().collect();
+    nodes.sort();
+
+    let mut roots = Vec::with_capacity(1);
+    if maze.contains(&start) {
+        roots.push(start);
+    }
+
+    let mut parent: HashMap<Node, Node> = HashMap::new();
+    let mut depth: HashMap<Node, usize> = HashMap::new();
+    let mut isolated_regions = Vec::new();
+
+    for root in roots.into_iter().chain(nodes.iter().cloned()) {
+        if depth.contains_key(&root) {
+            continue;
+        }
+
+        let region = spanning_tree(root, maze, &mut parent, &mut depth);
+        if root != start {
+            isolated_regions.push(region);
+        }
+    }
+
+    let mut links = 0;
+    let mut cycles = Vec::new();
+
+    for &node in &nodes {
+        for &next in &[(node.0 + 1, node.1), (node.0, node.1 + 1)] {
+            if !maze.contains(&next) {
+                continue;
+            }
+
+            links += 1;
+
+            if parent.get(&next) != Some(&node) && parent.get(&node) != Some(&next) {
+                cycles.push(cycle(node, next, &parent, &depth));
+            }
+        }
+    }
+
+    Validation {
+        passages: maze.len(),
+        links,
+        border_closed,
+        missing_cells,
+        isolated_regions,
+        cycles,
+    }
+}
+
+// Breadth first search out from the root, recording how each node was reached
+fn spanning_tree(
+    root: Node,
+    maze: &HashSet<Node>,
+    parent: &mut HashMap<Node, Node>,
+    depth: &mut HashMap<Node, usize>,
+) -> Vec<Node> {
+    let mut region = vec![root];
+    let mut queue = VecDeque::new();
+
+    depth.insert(root, 0);
+    queue.push_back(root);
+
+    while let Some(current) = queue.pop_front() {
+        let next_depth = depth[&current] + 1;
+
+        for &neighbour in neighbours(current, maze) {
+            if let Entry::Vacant(entry) = depth.entry(neighbour) {
+                entry.insert(next_depth);
+                parent.insert(neighbour, current);
+                region.push(neighbour);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    region.sort();
+    region
+}
+
+// The loop closed by linking two nodes, found by climbing the tree from both
+// until they meet
+fn cycle(
+    a: Node,
+    b: Node,
+    parent: &HashMap<Node, Node>,
+    depth: &HashMap<Node, usize>,
+) -> Vec<Node> {
+    let mut from_a = vec![a];
+    let mut from_b = vec![b];
+    let (mut a, mut b) = (a, b);
+
+    while a != b {
+        if depth[&a] >= depth[&b] {
+            a = parent[&a];
+            from_a.push(a);
+        } else {
+            b = parent[&b];
+            from_b.push(b);
+        }
+    }
+
+    // Both halves end at the node where they meet
+    from_b.pop();
+    from_a.extend(from_b.into_iter().rev());
+    from_a
+}
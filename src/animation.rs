@@ -0,0 +1,404 @@
+// The image crate only writes single frame GIFs and no APNG at all, so
+// animations go straight to the gif and deflate crates it is built on.
+extern crate deflate;
+extern crate gif;
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Write};
+
+use crc;
+
+// Palette indexes used for every frame
+const WALL: u8 = 0;
+const PASSAGE: u8 = 1;
+const HEAD: u8 = 2;
+const EXPLORED: u8 = 3;
+const PATH: u8 = 4;
+
+const PALETTE: [u8; 15] = [
+    0, 0, 0, // wall
+    255, 255, 255, // passage
+    255, 0, 0, // head
+    170, 210, 255, // explored
+    0, 0, 255, // path
+];
+
+/// File formats an animation can be written as.
+pub enum AnimationFormat {
+    Gif,
+    Apng,
+}
+
+impl AnimationFormat {
+    /// File extension animations in this format are saved with.
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            AnimationFormat::Gif => "gif",
+            AnimationFormat::Apng => "png",
+        }
+    }
+}
+
+/// Settings for recording an animation.
+pub struct AnimationOptions {
+    /// Number of steps to play between each frame that is written
+    pub frame_skip: usize,
+    /// Time each frame is shown for, in milliseconds
+    pub delay: u16,
+    /// Size of each maze pixel in the animation
+    pub scale: u32,
+    pub format: AnimationFormat,
+}
+
+impl AnimationOptions {
+    pub fn new(format: AnimationFormat) -> AnimationOptions {
+        AnimationOptions {
+            frame_skip: 1,
+            delay: 40,
+            scale: 4,
+            format,
+        }
+    }
+}
+
+// A single frame of palette indexes, one per maze pixel
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(height: u32, width: u32) -> Canvas {
+        Canvas {
+            width,
+            height,
+            pixels: vec![WALL; (width * height) as usize],
+        }
+    }
+
+    fn set(&mut self, node: (u32, u32), colour: u8) {
+        let index = (node.0 + node.1 * self.width) as usize;
+        self.pixels[index] = colour;
+    }
+
+    // Blows the canvas up so that each maze pixel is a square of scale pixels
+    fn scaled(&self, scale: u32) -> Vec<u8> {
+        let scale = scale.max(1);
+        let mut scaled = Vec::with_capacity((self.width * self.height * scale * scale) as usize);
+
+        for y in 0..self.height * scale {
+            for x in 0..self.width * scale {
+                let index = (x / scale + (y / scale) * self.width) as usize;
+                scaled.push(self.pixels[index]);
+            }
+        }
+
+        scaled
+    }
+}
+
+pub fn animate_generation(
+    height: u32,
+    width: u32,
+    initial: &[(u32, u32)],
+    steps: &[(u32, u32)],
+    options: &AnimationOptions,
+    filename: &str,
+) -> io::Result<()> {
+    let mut canvas = Canvas::new(height, width);
+    let skip = options.frame_skip.max(1);
+    let count = frame_count(steps.len(), skip);
+    let mut frames = FrameWriter::new(File::create(filename)?, &canvas, count, options)?;
+
+    // Some generators start with passages already open
+    for node in initial {
+        canvas.set(*node, PASSAGE);
+    }
+    frames.write(&canvas)?;
+
+    // Carve out each passage in turn, showing where the generator is working
+    for (index, node) in steps.iter().enumerate() {
+        canvas.set(*node, HEAD);
+
+        if (index + 1) % skip == 0 || index + 1 == steps.len() {
+            frames.write(&canvas)?;
+        }
+
+        canvas.set(*node, PASSAGE);
+    }
+    frames.write(&canvas)?;
+
+    frames.finish()
+}
+
+pub fn animate_solve(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    trace: &[(u32, u32)],
+    path: &[&(u32, u32)],
+    options: &AnimationOptions,
+    filename: &str,
+) -> io::Result<()> {
+    let mut canvas = Canvas::new(height, width);
+    let skip = options.frame_skip.max(1);
+    let count = frame_count(trace.len(), skip);
+    let mut frames = FrameWriter::new(File::create(filename)?, &canvas, count, options)?;
+
+    for node in node_map {
+        canvas.set(*node, PASSAGE);
+    }
+    frames.write(&canvas)?;
+
+    // Fill in each node as the solver looks at it
+    for (index, node) in trace.iter().enumerate() {
+        canvas.set(*node, HEAD);
+
+        if (index + 1) % skip == 0 || index + 1 == trace.len() {
+            frames.write(&canvas)?;
+        }
+
+        canvas.set(*node, EXPLORED);
+    }
+
+    // Finish with the path that was found
+    for node in path {
+        canvas.set(**node, PATH);
+    }
+    frames.write(&canvas)?;
+
+    frames.finish()
+}
+
+// Frames written for a run of steps: the one before they start, one for
+// every skip steps and any left over, and the finished maze
+fn frame_count(steps: usize, skip: usize) -> usize {
+    steps.div_ceil(skip) + 2
+}
+
+// Encodes each frame as soon as it is drawn, so that only one is ever held
+// in memory
+struct FrameWriter<W: Write> {
+    encoder: Encoder<W>,
+    width: u32,
+    height: u32,
+    scale: u32,
+    delay: u16,
+}
+
+enum Encoder<W: Write> {
+    Gif(gif::Encoder<W>),
+    // Frame control and frame data chunks share a sequence number
+    Apng { w: W, sequence: u32 },
+}
+
+impl<W: Write> FrameWriter<W> {
+    // APNG needs to know how many frames there will be before the first one
+    fn new(
+        mut w: W,
+        canvas: &Canvas,
+        frames: usize,
+        options: &AnimationOptions,
+    ) -> io::Result<FrameWriter<W>> {
+        let scale = options.scale.max(1);
+        let (width, height) = (canvas.width * scale, canvas.height * scale);
+
+        let encoder = match options.format {
+            AnimationFormat::Gif => Encoder::Gif(start_gif(w, width, height)?),
+            AnimationFormat::Apng => {
+                start_apng(&mut w, width, height, frames)?;
+                Encoder::Apng { w, sequence: 0 }
+            }
+        };
+
+        Ok(FrameWriter {
+            encoder,
+            width,
+            height,
+            scale,
+            delay: options.delay,
+        })
+    }
+
+    fn write(&mut self, canvas: &Canvas) -> io::Result<()> {
+        let pixels = canvas.scaled(self.scale);
+
+        match self.encoder {
+            Encoder::Gif(ref mut encoder) => {
+                write_gif_frame(encoder, self.width, self.height, &pixels, self.delay)
+            }
+            Encoder::Apng {
+                ref mut w,
+                ref mut sequence,
+            } => write_apng_frame(w, sequence, self.width, self.height, &pixels, self.delay),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self.encoder {
+            // The GIF trailer is written when the encoder is dropped
+            Encoder::Gif(_) => Ok(()),
+            Encoder::Apng { mut w, .. } => write_chunk(&mut w, b"IEND", &[]),
+        }
+    }
+}
+
+fn start_gif<W: Write>(w: W, width: u32, height: u32) -> io::Result<gif::Encoder<W>> {
+    if width > u32::from(u16::MAX) || height > u32::from(u16::MAX) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "GIF animations can be at most 65535 pixels across",
+        ));
+    }
+
+    let mut encoder = gif::Encoder::new(w, width as u16, height as u16, &PALETTE)?;
+    encoder.write_extension(gif::ExtensionData::Repetitions(gif::Repeat::Infinite))?;
+
+    Ok(encoder)
+}
+
+fn write_gif_frame<W: Write>(
+    encoder: &mut gif::Encoder<W>,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    delay: u16,
+) -> io::Result<()> {
+    let frame = gif::Frame {
+        width: width as u16,
+        height: height as u16,
+        // GIF delays are in hundredths of a second
+        delay: delay / 10,
+        buffer: Cow::Borrowed(pixels),
+        ..gif::Frame::default()
+    };
+
+    encoder.write_frame(&frame)
+}
+
+fn start_apng<W: Write>(w: &mut W, width: u32, height: u32, frames: usize) -> io::Result<()> {
+    w.write_all(&[137, 80, 78, 71, 13, 10, 26, 10])?;
+
+    // 8 bit palette image, no interlacing
+    let mut header = Vec::new();
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 3, 0, 0, 0]);
+    write_chunk(w, b"IHDR", &header)?;
+    write_chunk(w, b"PLTE", &PALETTE)?;
+
+    // Frame count, then play forever
+    let mut control = Vec::new();
+    control.extend_from_slice(&(frames as u32).to_be_bytes());
+    control.extend_from_slice(&0u32.to_be_bytes());
+    write_chunk(w, b"acTL", &control)
+}
+
+fn write_apng_frame<W: Write>(
+    w: &mut W,
+    sequence: &mut u32,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    delay: u16,
+) -> io::Result<()> {
+    // The first frame doubles as the still image
+    let first = *sequence == 0;
+
+    let mut frame_control = Vec::new();
+    frame_control.extend_from_slice(&sequence.to_be_bytes());
+    frame_control.extend_from_slice(&width.to_be_bytes());
+    frame_control.extend_from_slice(&height.to_be_bytes());
+    frame_control.extend_from_slice(&0u32.to_be_bytes());
+    frame_control.extend_from_slice(&0u32.to_be_bytes());
+    frame_control.extend_from_slice(&delay.to_be_bytes());
+    frame_control.extend_from_slice(&1000u16.to_be_bytes());
+    frame_control.extend_from_slice(&[0, 0]);
+    write_chunk(w, b"fcTL", &frame_control)?;
+    *sequence += 1;
+
+    // Each row starts with a filter type, which is always none here
+    let mut raw = Vec::with_capacity(pixels.len() + height as usize);
+    for row in pixels.chunks(width as usize) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    let compressed = deflate::deflate_bytes_zlib(&raw);
+
+    if first {
+        write_chunk(w, b"IDAT", &compressed)
+    } else {
+        let mut data = Vec::with_capacity(compressed.len() + 4);
+        data.extend_from_slice(&sequence.to_be_bytes());
+        data.extend_from_slice(&compressed);
+        *sequence += 1;
+        write_chunk(w, b"fdAT", &data)
+    }
+}
+
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+
+    let mut crc = crc::update(0xFFFF_FFFF, kind);
+    crc = crc::update(crc, data);
+    w.write_all(&(crc ^ 0xFFFF_FFFF).to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate image;
+
+    use self::image::GenericImage;
+    use super::*;
+
+    // Writes a single passage, then the same passage lit up as the head
+    fn record(format: AnimationFormat) -> Vec<u8> {
+        let mut options = AnimationOptions::new(format);
+        options.scale = 2;
+
+        let mut bytes = Vec::new();
+        let mut canvas = Canvas::new(3, 3);
+        {
+            let mut frames = FrameWriter::new(&mut bytes, &canvas, 2, &options).unwrap();
+            canvas.set((1, 1), PASSAGE);
+            frames.write(&canvas).unwrap();
+            canvas.set((1, 1), HEAD);
+            frames.write(&canvas).unwrap();
+            frames.finish().unwrap();
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn test_apng_first_frame_is_still_image() {
+        let bytes = record(AnimationFormat::Apng);
+
+        let img = image::load_from_memory_with_format(&bytes, image::PNG).unwrap();
+        assert_eq!(img.dimensions(), (6, 6));
+        assert_eq!(img.get_pixel(0, 0).data, [0, 0, 0, 255]);
+        assert_eq!(img.get_pixel(2, 3).data, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_gif_frames() {
+        let bytes = record(AnimationFormat::Gif);
+
+        let img = image::load_from_memory_with_format(&bytes, image::GIF).unwrap();
+        assert_eq!(img.dimensions(), (6, 6));
+        assert_eq!(img.get_pixel(3, 2).data, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_frame_count() {
+        // Before, every second step and the odd one left over, then after
+        assert_eq!(frame_count(5, 2), 5);
+        assert_eq!(frame_count(4, 2), 4);
+        assert_eq!(frame_count(0, 3), 2);
+    }
+}
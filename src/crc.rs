@@ -0,0 +1,37 @@
+// CRC-32 as used by PNG chunks and the binary maze format
+
+/// Checksum of a whole run of bytes.
+pub fn checksum(bytes: &[u8]) -> u32 {
+    update(0xFFFF_FFFF, bytes) ^ 0xFFFF_FFFF
+}
+
+/// Feeds more bytes into a running checksum. Start from `0xFFFF_FFFF` and
+/// flip every bit of the result once all the bytes are in.
+pub fn update(mut crc: u32, bytes: &[u8]) -> u32 {
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum(b"IEND"), 0xAE42_6082);
+        assert_eq!(
+            update(update(0xFFFF_FFFF, b"IE"), b"ND") ^ 0xFFFF_FFFF,
+            0xAE42_6082
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use analysis::{self, MazeStats};
+use mazebuilder::{self, Generator};
+
+// Number of times the braid ratio is halved towards the target for each seed
+const TUNING_STEPS: u32 = 8;
+
+/// Measurements a maze can be generated to hit.
+#[derive(Clone, Copy)]
+pub enum Metric {
+    /// The combined difficulty score
+    Difficulty,
+    /// Length of the shortest way through
+    SolutionLength,
+    /// Places on the shortest way through where there is a choice to make
+    DecisionPoints,
+    /// Average distance from a dead end back to its junction
+    DeadEndDepth,
+}
+
+impl Metric {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Metric::Difficulty => "difficulty",
+            Metric::SolutionLength => "solution length",
+            Metric::DecisionPoints => "decision points",
+            Metric::DeadEndDepth => "dead end depth",
+        }
+    }
+
+    fn measure(&self, stats: &MazeStats) -> f64 {
+        match *self {
+            Metric::Difficulty => stats.difficulty,
+            Metric::SolutionLength => stats.solution_length as f64,
+            Metric::DecisionPoints => stats.decision_points as f64,
+            Metric::DeadEndDepth => stats.average_dead_end_depth,
+        }
+    }
+}
+
+/// A band that one of a maze's measurements has to fall within, inclusive at
+/// both ends.
+pub struct Target {
+    pub metric: Metric,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Target {
+    /// A band from `low` to `high`, which can't be the wrong way round.
+    pub fn new(metric: Metric, low: f64, high: f64) -> Result<Target, String> {
+        if low.is_nan() || high.is_nan() {
+            return Err(format!(
+                "the {} band needs numbers at both ends, not {} and {}",
+                metric.name(),
+                low,
+                high
+            ));
+        }
+
+        if low > high {
+            return Err(format!(
+                "the lowest {} of {} is above the highest of {}",
+                metric.name(),
+                low,
+                high
+            ));
+        }
+
+        Ok(Target { metric, low, high })
+    }
+
+    fn contains(&self, value: f64) -> bool {
+        value >= self.low && value <= self.high
+    }
+}
+
+/// A maze that hit its target, along with everything needed to make it again.
+pub struct Level {
+    pub maze: HashSet<(u32, u32)>,
+    pub seed: u64,
+    /// Share of dead ends that were opened up into loops
+    pub braid: f32,
+    pub stats: MazeStats,
+    /// Number of mazes generated to find this one, counting this one itself
+    pub attempts: u32,
+}
+
+/// Generates a maze from each seed in turn, returning the first that falls
+/// within the target.
+pub fn find_level(gen: Generator, braid: f32, seeds: Range<u64>, target: &Target) -> Option<Level> {
+    let mut attempts = 0;
+
+    for seed in seeds {
+        attempts += 1;
+
+        let level = generate(gen, braid, seed, attempts);
+        if target.contains(target.metric.measure(&level.stats)) {
+            return Some(level);
+        }
+    }
+
+    None
+}
+
+/// Generates a maze from each seed in turn, searching for a braid ratio that
+/// brings it within the target.
+///
+/// Opening up more dead ends gives the solver more ways round, which usually
+/// makes mazes easier, so the ratio is raised while the maze is too hard and
+/// lowered while it is too easy.
+pub fn tune_braid(gen: Generator, seeds: Range<u64>, target: &Target) -> Option<Level> {
+    let mut attempts = 0;
+
+    for seed in seeds {
+        let (mut low, mut high) = (0.0, 1.0);
+        let mut braid = 0.0;
+
+        for _ in 0..TUNING_STEPS {
+            attempts += 1;
+
+            let level = generate(gen, braid, seed, attempts);
+            let value = target.metric.measure(&level.stats);
+            if target.contains(value) {
+                return Some(level);
+            }
+
+            // Dead ends can't be put back, so a perfect maze that is too
+            // easy won't get any harder
+            if value < target.low && braid == 0.0 {
+                break;
+            }
+
+            if value > target.high {
+                low = braid;
+            } else {
+                high = braid;
+            }
+            braid = (low + high) / 2.0;
+        }
+    }
+
+    None
+}
+
+fn generate(gen: Generator, braid: f32, seed: u64, attempts: u32) -> Level {
+    let (height, width) = gen.size();
+    let maze = mazebuilder::generate_maze_seeded(gen, braid, seed);
+    let stats = analysis::stats((1, 1), (width - 2, height - 2), &maze);
+
+    Level {
+        maze,
+        seed,
+        braid,
+        stats,
+        attempts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_level_in_band() {
+        let gen = Generator::Kruskal {
+            height: 31,
+            width: 31,
+        };
+        let target = Target::new(Metric::DecisionPoints, 15.0, 25.0).unwrap();
+
+        let level = find_level(gen, 0.0, 0..100, &target).expect("no maze was in the band");
+        assert!(level.stats.decision_points >= 15 && level.stats.decision_points <= 25);
+
+        // The seed makes exactly the same maze again
+        let again = mazebuilder::generate_maze_seeded(gen, level.braid, level.seed);
+        assert!(again == level.maze);
+    }
+
+    #[test]
+    fn test_first_seed_counts_as_an_attempt() {
+        let gen = Generator::DFS {
+            height: 11,
+            width: 11,
+        };
+        let target = Target::new(Metric::SolutionLength, 0.0, 1e9).unwrap();
+
+        assert_eq!(find_level(gen, 0.0, 0..5, &target).unwrap().attempts, 1);
+    }
+
+    #[test]
+    fn test_target_the_wrong_way_round() {
+        assert!(Target::new(Metric::Difficulty, 5.0, 5.0).is_ok());
+        assert!(Target::new(Metric::Difficulty, 6.0, 5.0)
+            .err()
+            .is_some_and(|message| message.contains("above")));
+
+        // A NaN isn't above or below anything, so is reported on its own
+        for &(low, high) in &[(f64::NAN, 5.0), (5.0, f64::NAN)] {
+            assert!(Target::new(Metric::Difficulty, low, high)
+                .err()
+                .is_some_and(|message| message.contains("needs numbers")));
+        }
+    }
+
+    #[test]
+    fn test_impossible_target() {
+        let gen = Generator::DFS {
+            height: 11,
+            width: 11,
+        };
+        let target = Target::new(Metric::SolutionLength, 1000.0, 2000.0).unwrap();
+
+        assert!(find_level(gen, 0.0, 0..5, &target).is_none());
+        assert!(tune_braid(gen, 0..2, &target).is_none());
+    }
+
+    #[test]
+    fn test_tune_braid_shortens_solution() {
+        let gen = Generator::DFS {
+            height: 41,
+            width: 41,
+        };
+        let everything = Target::new(Metric::SolutionLength, 0.0, 1e9).unwrap();
+        let perfect = find_level(gen, 0.0, 3..4, &everything).unwrap();
+
+        // Aim for a way through no more than two thirds as long
+        let high = perfect.stats.solution_length as f64 * 2.0 / 3.0;
+        let target = Target::new(Metric::SolutionLength, 0.0, high).unwrap();
+
+        let level = tune_braid(gen, 3..4, &target).expect("no braid ratio hit the target");
+        assert!(level.braid > 0.0);
+        assert!(level.stats.solution_length as f64 <= high);
+    }
+}
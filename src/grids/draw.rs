@@ -0,0 +1,517 @@
+extern crate image;
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use imagecontrol::{self, paint, ImageFormat, RenderOptions, Stroke};
+use svg::{self, SvgOptions};
+
+use super::{Edge, Grid, GridMaze, Point, Stair};
+
+// How far walls stop short of the side of a bridge, in cells
+const BRIDGE_GAP: f64 = 0.2;
+
+pub fn generate_grid_svg<G: Grid>(
+    maze: &GridMaze<G>,
+    path: Option<&[usize]>,
+    options: &SvgOptions,
+    filename: &str,
+) -> io::Result<()> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+
+    write_grid_svg(&mut writer, maze, path, options)
+}
+
+pub fn write_grid_svg<W: Write, G: Grid>(
+    w: &mut W,
+    maze: &GridMaze<G>,
+    path: Option<&[usize]>,
+    options: &SvgOptions,
+) -> io::Result<()> {
+    let cell = f64::from(options.cell_size);
+    let (width, height) = maze.grid.bounds();
+    let (full_width, full_height) = (width * cell, height * cell);
+
+    // Leave room for the outer walls, which are centred on the edge
+    let pad = f64::from(options.stroke_width) / 2.0;
+
+    writeln!(
+        w,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.2}" height="{:.2}" viewBox="{:.2} {:.2} {:.2} {:.2}">"#,
+        full_width + 2.0 * pad,
+        full_height + 2.0 * pad,
+        -pad,
+        -pad,
+        full_width + 2.0 * pad,
+        full_height + 2.0 * pad
+    )?;
+
+    if let Some(ref background) = options.background {
+        writeln!(
+            w,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"/>"#,
+            -pad,
+            -pad,
+            full_width + 2.0 * pad,
+            full_height + 2.0 * pad,
+            svg::escape(background)
+        )?;
+    }
+
+    let walls: Vec<String> = walls(maze)
+        .iter()
+        .map(|edge| {
+            let (from, to) = (edge.start(), edge.end());
+            let line = match *edge {
+                Edge::Line(..) => format!("L{:.2},{:.2}", to.0 * cell, to.1 * cell),
+                Edge::Arc {
+                    radius,
+                    from: start,
+                    to: end,
+                    ..
+                } => {
+                    // Going clockwise is the positive direction for SVG too
+                    let large = if (end - start).abs() > PI { 1 } else { 0 };
+                    let sweep = if end > start { 1 } else { 0 };
+                    format!(
+                        "A{:.2},{:.2} 0 {} {} {:.2},{:.2}",
+                        radius * cell,
+                        radius * cell,
+                        large,
+                        sweep,
+                        to.0 * cell,
+                        to.1 * cell
+                    )
+                }
+            };
+
+            format!("M{:.2},{:.2} {}", from.0 * cell, from.1 * cell, line)
+        })
+        .collect();
+
+    writeln!(
+        w,
+        r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round"/>"#,
+        walls.join(" "),
+        svg::escape(&options.wall_colour),
+        options.stroke_width
+    )?;
+
+    let markers: Vec<String> = stair_markers(maze)
+        .iter()
+        .map(|corners| {
+            let points: Vec<String> = corners
+                .iter()
+                .map(|&(x, y)| format!("{:.2},{:.2}", x * cell, y * cell))
+                .collect();
+            format!("M{} Z", points.join(" L"))
+        })
+        .collect();
+
+    if !markers.is_empty() {
+        writeln!(
+            w,
+            r#"<path d="{}" fill="{}"/>"#,
+            markers.join(" "),
+            svg::escape(&options.wall_colour)
+        )?;
+    }
+
+    if let Some(path) = path {
+        let steps: Vec<String> = path
+            .iter()
+            .enumerate()
+            .map(|(index, &step)| {
+                let (x, y) = maze.grid.centre(step);
+
+                // Taking the stairs jumps to another part of the drawing, and
+                // a tunnel goes out of sight under a bridge
+                let joined = index > 0 && shares_side(&maze.grid, path[index - 1], step);
+                let command = if joined { "L" } else { "M" };
+                format!("{}{:.2},{:.2}", command, x * cell, y * cell)
+            })
+            .collect();
+
+        if !steps.is_empty() {
+            writeln!(
+                w,
+                r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                steps.join(" "),
+                svg::escape(&options.path_colour),
+                options.path_width
+            )?;
+        }
+    }
+
+    writeln!(w, "</svg>")
+}
+
+pub fn generate_grid_image<G: Grid>(
+    maze: &GridMaze<G>,
+    path: Option<&[usize]>,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    let img_buffer = render_grid_image(maze, path, options);
+
+    // Send the image
+    imagecontrol::save_image(image::ImageRgb8(img_buffer), filename, format)
+}
+
+fn render_grid_image<G: Grid>(
+    maze: &GridMaze<G>,
+    path: Option<&[usize]>,
+    options: &RenderOptions,
+) -> image::RgbImage {
+    // Each cell is as wide as a passage and a wall on the square grid
+    let scale = (options.passage_width + options.wall_thickness) as f32;
+    let margin = options.margin as f32;
+    let (width, height) = maze.grid.bounds();
+    let image_width = (width as f32 * scale + 2.0 * margin).ceil() as u32;
+    let image_height = (height as f32 * scale + 2.0 * margin).ceil() as u32;
+
+    let to_pixels = |point: Point| {
+        (
+            margin + point.0 as f32 * scale,
+            margin + point.1 as f32 * scale,
+        )
+    };
+
+    let background = options.background.unwrap_or(options.passage_colour);
+    let mut img_buffer =
+        image::ImageBuffer::from_pixel(image_width, image_height, image::Rgb(background));
+
+    // Fill in the inside of every cell, anything left over is outside the maze
+    if options.background.is_some() {
+        for cell in 0..maze.grid.cell_count() {
+            let corners: Vec<(f32, f32)> = maze
+                .grid
+                .sides(cell)
+                .into_iter()
+                .flat_map(|(_, edge)| edge.points())
+                .map(to_pixels)
+                .collect();
+            fill_polygon(&mut img_buffer, &corners, options.passage_colour);
+        }
+    }
+
+    // Curved walls are drawn as lots of short straight ones
+    let mut strokes: Vec<Stroke> = Vec::new();
+    for edge in walls(maze) {
+        for pair in edge.points().windows(2) {
+            strokes.push((to_pixels(pair[0]), to_pixels(pair[1]), options.wall_colour));
+        }
+    }
+    paint(
+        &mut img_buffer,
+        &strokes,
+        options.wall_thickness as f32 / 2.0,
+        options.antialias,
+    );
+
+    for corners in stair_markers(maze) {
+        let corners: Vec<(f32, f32)> = corners.into_iter().map(to_pixels).collect();
+        fill_polygon(&mut img_buffer, &corners, options.wall_colour);
+    }
+
+    // The solution is half as wide as a passage, fading from red to blue
+    // unless it has a colour of its own
+    if let Some(path) = path {
+        let strokes: Vec<Stroke> = path
+            .windows(2)
+            .enumerate()
+            .filter(|&(_, pair)| shares_side(&maze.grid, pair[0], pair[1]))
+            .map(|(index, pair)| {
+                let colour = options.path_colour.unwrap_or_else(|| {
+                    let percent = index as f32 / path.len() as f32;
+                    let color_b = (255f32 * percent) as u8;
+                    [255 - color_b, 0, color_b]
+                });
+
+                (
+                    to_pixels(maze.grid.centre(pair[0])),
+                    to_pixels(maze.grid.centre(pair[1])),
+                    colour,
+                )
+            })
+            .collect();
+        let radius = (options.passage_width as f32 / 4f32).max(0.5);
+        paint(&mut img_buffer, &strokes, radius, options.antialias);
+    }
+
+    img_buffer
+}
+
+// Every wall still standing, each one listed once. Walls that run into the
+// side of a bridge stop short of it, so the tunnel can be seen going under.
+fn walls<G: Grid>(maze: &GridMaze<G>) -> Vec<Edge> {
+    let mut walls = Vec::new();
+    let bridges = bridge_sides(maze);
+
+    for cell in 0..maze.grid.cell_count() {
+        for (neighbour, edge) in maze.grid.sides(cell) {
+            let standing = match neighbour {
+                None => true,
+                Some(neighbour) => cell < neighbour && !maze.is_linked(cell, neighbour),
+            };
+
+            if standing {
+                walls.push(match edge {
+                    Edge::Line(from, to) => Edge::Line(
+                        short_of_bridge(&bridges, from, to),
+                        short_of_bridge(&bridges, to, from),
+                    ),
+                    arc => arc,
+                });
+            }
+        }
+    }
+
+    walls
+}
+
+// Sides of cells with a tunnel running under them, where the tunnel goes in
+// and comes out. Each side is filed under both of its ends, by the way it runs.
+fn bridge_sides<G: Grid>(maze: &GridMaze<G>) -> HashMap<(i64, i64), Vec<Point>> {
+    let mut bridges = HashMap::new();
+
+    for cell in 0..maze.grid.cell_count() {
+        for (under, beyond) in maze.grid.tunnels(cell) {
+            if !maze.is_linked(cell, beyond) {
+                continue;
+            }
+
+            for (neighbour, edge) in maze.grid.sides(cell) {
+                if neighbour == Some(under) {
+                    let (from, to) = (edge.start(), edge.end());
+                    let along = (to.0 - from.0, to.1 - from.1);
+
+                    for &end in &[from, to] {
+                        bridges
+                            .entry(endpoint(end))
+                            .or_insert_with(Vec::new)
+                            .push(along);
+                    }
+                }
+            }
+        }
+    }
+
+    bridges
+}
+
+// Where a point is, rounded so the ends of neighbouring sides match up
+fn endpoint(point: Point) -> (i64, i64) {
+    (
+        (point.0 * 1e6).round() as i64,
+        (point.1 * 1e6).round() as i64,
+    )
+}
+
+// Moves the end of a wall back along it if it meets the side of a bridge
+// at an angle
+fn short_of_bridge(bridges: &HashMap<(i64, i64), Vec<Point>>, end: Point, other: Point) -> Point {
+    let (dx, dy) = (other.0 - end.0, other.1 - end.1);
+    let length = (dx * dx + dy * dy).sqrt();
+
+    let meets = bridges.get(&endpoint(end)).is_some_and(|sides| {
+        sides
+            .iter()
+            .any(|&(sx, sy)| (dx * sy - dy * sx).abs() >= 1e-9)
+    });
+
+    if meets && length > BRIDGE_GAP {
+        (
+            end.0 + dx * BRIDGE_GAP / length,
+            end.1 + dy * BRIDGE_GAP / length,
+        )
+    } else {
+        end
+    }
+}
+
+// A small triangle for each open flight of stairs, pointing up in the top
+// half of the cell or down in the bottom half
+fn stair_markers<G: Grid>(maze: &GridMaze<G>) -> Vec<Vec<Point>> {
+    let mut markers = Vec::new();
+
+    for cell in 0..maze.grid.cell_count() {
+        let (x, y) = maze.grid.centre(cell);
+
+        for (neighbour, stair) in maze.grid.stairs(cell) {
+            if !maze.is_linked(cell, neighbour) {
+                continue;
+            }
+
+            markers.push(match stair {
+                Stair::Up => vec![(x, y - 0.35), (x + 0.2, y - 0.1), (x - 0.2, y - 0.1)],
+                Stair::Down => vec![(x - 0.2, y + 0.1), (x + 0.2, y + 0.1), (x, y + 0.35)],
+            });
+        }
+    }
+
+    markers
+}
+
+fn shares_side<G: Grid>(grid: &G, a: usize, b: usize) -> bool {
+    grid.sides(a)
+        .iter()
+        .any(|&(neighbour, _)| neighbour == Some(b))
+}
+
+// Colours every pixel whose middle is inside the polygon, which can be any
+// shape so long as its sides don't cross
+fn fill_polygon(img_buffer: &mut image::RgbImage, corners: &[(f32, f32)], colour: [u8; 3]) {
+    let (image_width, image_height) = img_buffer.dimensions();
+    let left = corners
+        .iter()
+        .fold(f32::MAX, |min, c| min.min(c.0))
+        .max(0f32) as u32;
+    let right = (corners.iter().fold(0f32, |max, c| max.max(c.0)).ceil() as u32).min(image_width);
+    let top = corners
+        .iter()
+        .fold(f32::MAX, |min, c| min.min(c.1))
+        .max(0f32) as u32;
+    let bottom = (corners.iter().fold(0f32, |max, c| max.max(c.1)).ceil() as u32).min(image_height);
+
+    for py in top..bottom {
+        for px in left..right {
+            let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+
+            // Count the sides crossed heading right from the pixel, an odd
+            // number means it started inside
+            let crossings = corners
+                .iter()
+                .zip(corners.iter().cycle().skip(1))
+                .filter(|&(a, b)| {
+                    (a.1 > y) != (b.1 > y) && x < a.0 + (y - a.1) * (b.0 - a.0) / (b.1 - a.1)
+                })
+                .count();
+
+            if crossings % 2 == 1 {
+                img_buffer.put_pixel(px, py, image::Rgb(colour));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{HexGrid, LevelGrid, PolarGrid, WeaveGrid};
+    use super::*;
+    use imagecontrol::Theme;
+
+    #[test]
+    fn test_grid_svg_walls() {
+        // Two hexagons side by side with the wall between them knocked down
+        let mut maze = GridMaze::new(HexGrid::new(2, 1));
+        maze.link(0, 1);
+
+        let mut bytes = Vec::new();
+        write_grid_svg(&mut bytes, &maze, Some(&[0, 1]), &SvgOptions::new()).unwrap();
+        let svg = String::from_utf8(bytes).unwrap();
+
+        // Twelve sides less the two halves of the shared one
+        assert_eq!(svg.matches(" L").count() - 1, 10);
+        assert!(svg.contains("M5.00,4.33 L12.50,8.66"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_grid_image_background() {
+        let maze = GridMaze::new(HexGrid::new(3, 3));
+        let mut options = RenderOptions::new(Theme::Classic);
+        options.background = Some([0, 255, 0]);
+
+        let img = render_grid_image(&maze, None, &options);
+
+        let scale = (options.passage_width + options.wall_thickness) as f64;
+        let pixel = |(x, y): Point| {
+            let px = options.margin + (x * scale) as u32;
+            let py = options.margin + (y * scale) as u32;
+            img.get_pixel(px, py).data
+        };
+
+        // Under the first column is outside every hexagon, the middles are inside
+        let (x, _) = maze.grid.centre(6);
+        assert_eq!(pixel((x, maze.grid.bounds().1 - 0.1)), [0, 255, 0]);
+        assert_eq!(pixel(maze.grid.centre(4)), [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_polar_svg_arcs() {
+        let maze = GridMaze::new(PolarGrid::new(2));
+
+        let mut bytes = Vec::new();
+        write_grid_svg(&mut bytes, &maze, None, &SvgOptions::new()).unwrap();
+        let svg = String::from_utf8(bytes).unwrap();
+
+        // Six arcs round the middle, six round the outside and six spokes
+        assert_eq!(svg.matches(" A").count(), 12);
+        assert_eq!(svg.matches(" L").count(), 6);
+
+        // The first arc round the outside goes clockwise from the right
+        assert!(svg.contains("M40.00,20.00 A20.00,20.00 0 0 1 30.00,37.32"));
+    }
+
+    #[test]
+    fn test_polar_image_is_round() {
+        let maze = GridMaze::new(PolarGrid::new(3));
+        let mut options = RenderOptions::new(Theme::Classic);
+        options.background = Some([0, 255, 0]);
+
+        let img = render_grid_image(&maze, None, &options);
+        let (width, height) = img.dimensions();
+
+        // The corners are outside the circle, the middle is inside
+        assert_eq!(
+            img.get_pixel(options.margin + 2, options.margin + 2).data,
+            [0, 255, 0]
+        );
+        assert_eq!(
+            img.get_pixel(width / 2 + 3, height / 2 + 3).data,
+            [255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_level_svg_stairs() {
+        // Two levels of a single cell, joined by stairs
+        let mut maze = GridMaze::new(LevelGrid::new(1, 1, 2));
+        maze.link(0, 1);
+
+        let mut bytes = Vec::new();
+        write_grid_svg(&mut bytes, &maze, Some(&[0, 1]), &SvgOptions::new()).unwrap();
+        let svg = String::from_utf8(bytes).unwrap();
+
+        // One marker going up from the first level and one coming down to
+        // the second, with the solution jumping between them
+        assert!(svg.contains(r#"<path d="M5.00,1.50 L7.00,4.00 L3.00,4.00 Z M23.00,6.00"#));
+        assert!(svg.contains(r#"<path d="M5.00,5.00 M25.00,5.00""#));
+    }
+
+    #[test]
+    fn test_weave_svg_bridge_gap() {
+        // A passage down the middle of a three by three grid, with a tunnel
+        // under it from left to right
+        let mut maze = GridMaze::new(WeaveGrid::new(3, 3));
+        maze.link(1, 4);
+        maze.link(4, 7);
+        maze.link(3, 5);
+        assert!(maze.is_crossing(4));
+
+        let mut bytes = Vec::new();
+        write_grid_svg(&mut bytes, &maze, None, &SvgOptions::new()).unwrap();
+        let svg = String::from_utf8(bytes).unwrap();
+
+        // The sides of the bridge are whole, the walls of the tunnel stop
+        // short of them
+        assert!(svg.contains("M10.00,10.00 L10.00,20.00"));
+        assert!(svg.contains("M8.00,10.00 L0.00,10.00"));
+        assert!(svg.contains("M30.00,20.00 L22.00,20.00"));
+    }
+}
@@ -0,0 +1,160 @@
+use super::{Edge, Grid, Point};
+
+// Height of a hexagon that is one cell wide from corner to corner
+const HEX_HEIGHT: f64 = 0.866_025_403_784_438_6;
+
+/// A grid of flat topped hexagons, each with up to six neighbours.
+///
+/// Cells are counted in columns and rows, with every odd column shifted half
+/// a hexagon down, so cell `(column, row)` is number `row * columns + column`.
+pub struct HexGrid {
+    columns: u32,
+    rows: u32,
+}
+
+impl HexGrid {
+    pub fn new(columns: u32, rows: u32) -> HexGrid {
+        HexGrid {
+            columns: columns.max(1),
+            rows: rows.max(1),
+        }
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    fn cell(&self, column: i64, row: i64) -> Option<usize> {
+        if column < 0 || row < 0 || column >= i64::from(self.columns) || row >= i64::from(self.rows)
+        {
+            return None;
+        }
+
+        Some((row * i64::from(self.columns) + column) as usize)
+    }
+
+    // The cells across each side, clockwise from the bottom right. Odd
+    // columns sit lower, so their diagonal neighbours are a row further down.
+    fn around(&self, cell: usize) -> [Option<usize>; 6] {
+        let column = (cell % self.columns as usize) as i64;
+        let row = (cell / self.columns as usize) as i64;
+        let (upper, lower) = if column % 2 == 0 {
+            (row - 1, row)
+        } else {
+            (row, row + 1)
+        };
+
+        [
+            self.cell(column + 1, lower),
+            self.cell(column, row + 1),
+            self.cell(column - 1, lower),
+            self.cell(column - 1, upper),
+            self.cell(column, row - 1),
+            self.cell(column + 1, upper),
+        ]
+    }
+}
+
+impl Grid for HexGrid {
+    fn cell_count(&self) -> usize {
+        (self.columns * self.rows) as usize
+    }
+
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Edge)> {
+        let (x, y) = self.centre(cell);
+
+        // Corners clockwise from the right hand point, with y pointing down
+        let corners: Vec<Point> = (0..6)
+            .map(|corner| {
+                let angle = f64::from(corner) * std::f64::consts::PI / 3.0;
+                (x + 0.5 * angle.cos(), y + 0.5 * angle.sin())
+            })
+            .collect();
+
+        self.around(cell)
+            .iter()
+            .enumerate()
+            .map(|(side, &neighbour)| {
+                (
+                    neighbour,
+                    Edge::Line(corners[side], corners[(side + 1) % 6]),
+                )
+            })
+            .collect()
+    }
+
+    fn centre(&self, cell: usize) -> Point {
+        let column = cell % self.columns as usize;
+        let row = cell / self.columns as usize;
+        let shift = if column.is_multiple_of(2) { 0.0 } else { 0.5 };
+
+        (
+            0.5 + 0.75 * column as f64,
+            HEX_HEIGHT * (row as f64 + 0.5 + shift),
+        )
+    }
+
+    fn bounds(&self) -> Point {
+        let shift = if self.columns > 1 { 0.5 } else { 0.0 };
+
+        (
+            0.25 + 0.75 * f64::from(self.columns),
+            HEX_HEIGHT * (f64::from(self.rows) + shift),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_neighbours() {
+        let grid = HexGrid::new(4, 3);
+
+        // (1, 1) is in a lower column, so it reaches down into row 2
+        let mut neighbours = grid.neighbours(5);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![1, 4, 6, 8, 9, 10]);
+
+        // (2, 1) is in a higher column, so it reaches up into row 0
+        let mut neighbours = grid.neighbours(6);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![1, 2, 3, 5, 7, 10]);
+
+        // Corners only touch a couple of cells
+        let mut neighbours = grid.neighbours(0);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![1, 4]);
+    }
+
+    #[test]
+    fn test_hex_sides_are_shared() {
+        let grid = HexGrid::new(5, 4);
+
+        // Both cells either side of a wall draw it in the same place
+        for cell in 0..grid.cell_count() {
+            for (neighbour, edge) in grid.sides(cell) {
+                let neighbour = match neighbour {
+                    Some(neighbour) => neighbour,
+                    None => continue,
+                };
+
+                let (from, to) = (edge.start(), edge.end());
+                let matching = grid.sides(neighbour).into_iter().any(|(back, edge)| {
+                    let (a, b) = (edge.start(), edge.end());
+                    back == Some(cell)
+                        && (a.0 - to.0).abs() < 1e-9
+                        && (a.1 - to.1).abs() < 1e-9
+                        && (b.0 - from.0).abs() < 1e-9
+                        && (b.1 - from.1).abs() < 1e-9
+                });
+                assert!(matching, "cells {} and {} disagree", cell, neighbour);
+            }
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use super::{Direction, Edge, Grid, Point, Stair};
+
+/// A square grid stacked up into levels, like the floors of a building.
+///
+/// Cells are numbered level by level from the bottom, row by row within
+/// each level, so cell `(column, row, level)` is number
+/// `(level * rows + row) * columns + column`. Each level is drawn as its own
+/// panel, with the panels laid out in a grid from the top left.
+pub struct LevelGrid {
+    columns: u32,
+    rows: u32,
+    levels: u32,
+}
+
+impl LevelGrid {
+    pub fn new(columns: u32, rows: u32, levels: u32) -> LevelGrid {
+        LevelGrid {
+            columns: columns.max(1),
+            rows: rows.max(1),
+            levels: levels.max(1),
+        }
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn levels(&self) -> u32 {
+        self.levels
+    }
+
+    /// Column, row and level of a cell.
+    pub fn position(&self, cell: usize) -> (u32, u32, u32) {
+        let (columns, rows) = (self.columns as usize, self.rows as usize);
+
+        (
+            (cell % columns) as u32,
+            (cell / columns % rows) as u32,
+            (cell / (columns * rows)) as u32,
+        )
+    }
+
+    pub fn cell_at(&self, column: u32, row: u32, level: u32) -> Option<usize> {
+        if column >= self.columns || row >= self.rows || level >= self.levels {
+            return None;
+        }
+
+        Some(((level * self.rows + row) * self.columns + column) as usize)
+    }
+
+    // Number of panels across, as near to square as it can get
+    fn panel_columns(&self) -> u32 {
+        (f64::from(self.levels).sqrt().ceil() as u32).max(1)
+    }
+
+    // Top left corner of a cell on the drawing, leaving a cell's gap
+    // between the panels
+    fn corner(&self, cell: usize) -> Point {
+        let (column, row, level) = self.position(cell);
+        let panel = self.panel_columns();
+
+        (
+            f64::from(level % panel * (self.columns + 1) + column),
+            f64::from(level / panel * (self.rows + 1) + row),
+        )
+    }
+}
+
+impl Grid for LevelGrid {
+    fn cell_count(&self) -> usize {
+        (self.columns * self.rows * self.levels) as usize
+    }
+
+    /// The cell one step away in the given direction, if it is on the grid.
+    fn step(&self, cell: usize, direction: Direction) -> Option<usize> {
+        let (column, row, level) = self.position(cell);
+        let (dx, dy, dz) = direction.offset();
+        let (x, y, z) = (
+            i64::from(column) + dx,
+            i64::from(row) + dy,
+            i64::from(level) + dz,
+        );
+
+        if x < 0 || y < 0 || z < 0 {
+            return None;
+        }
+
+        self.cell_at(x as u32, y as u32, z as u32)
+    }
+
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Edge)> {
+        let (x, y) = self.corner(cell);
+
+        vec![
+            (
+                self.step(cell, Direction::North),
+                Edge::Line((x, y), (x + 1.0, y)),
+            ),
+            (
+                self.step(cell, Direction::East),
+                Edge::Line((x + 1.0, y), (x + 1.0, y + 1.0)),
+            ),
+            (
+                self.step(cell, Direction::South),
+                Edge::Line((x + 1.0, y + 1.0), (x, y + 1.0)),
+            ),
+            (
+                self.step(cell, Direction::West),
+                Edge::Line((x, y + 1.0), (x, y)),
+            ),
+        ]
+    }
+
+    fn stairs(&self, cell: usize) -> Vec<(usize, Stair)> {
+        let mut stairs = Vec::new();
+
+        if let Some(above) = self.step(cell, Direction::Up) {
+            stairs.push((above, Stair::Up));
+        }
+        if let Some(below) = self.step(cell, Direction::Down) {
+            stairs.push((below, Stair::Down));
+        }
+
+        stairs
+    }
+
+    fn centre(&self, cell: usize) -> Point {
+        let (x, y) = self.corner(cell);
+        (x + 0.5, y + 0.5)
+    }
+
+    fn bounds(&self) -> Point {
+        let panels = self.panel_columns();
+        let panel_rows = self.levels.div_ceil(panels);
+
+        (
+            f64::from(panels * (self.columns + 1) - 1),
+            f64::from(panel_rows * (self.rows + 1) - 1),
+        )
+    }
+
+    // Measured through the building rather than across the drawing, so
+    // every step costs one
+    fn distance(&self, a: usize, b: usize) -> f64 {
+        let (ax, ay, az) = self.position(a);
+        let (bx, by, bz) = self.position(b);
+        let (dx, dy, dz) = (
+            f64::from(ax) - f64::from(bx),
+            f64::from(ay) - f64::from(by),
+            f64::from(az) - f64::from(bz),
+        );
+
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mazebuilder::{generate_grid, Algorithm};
+    use rand::{SeedableRng, StdRng};
+
+    #[test]
+    fn test_level_steps() {
+        let grid = LevelGrid::new(4, 3, 2);
+
+        // (1, 2, 0) is on the bottom row of the lower level
+        let cell = grid.cell_at(1, 2, 0).unwrap();
+        assert_eq!(grid.position(cell), (1, 2, 0));
+        assert_eq!(grid.step(cell, Direction::North), grid.cell_at(1, 1, 0));
+        assert_eq!(grid.step(cell, Direction::South), None);
+        assert_eq!(grid.step(cell, Direction::Up), grid.cell_at(1, 2, 1));
+        assert_eq!(grid.step(cell, Direction::Down), None);
+
+        // Stairs count as neighbours, so generators and solvers use them
+        let mut neighbours = grid.neighbours(cell);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![5, 8, 10, 21]);
+        assert_eq!(grid.stairs(21), vec![(9, Stair::Down)]);
+    }
+
+    #[test]
+    fn test_level_panels() {
+        let grid = LevelGrid::new(4, 3, 3);
+
+        // Three levels go in a two by two grid of panels with a gap between
+        assert_eq!(grid.bounds(), (9.0, 7.0));
+        assert_eq!(grid.centre(grid.cell_at(0, 0, 1).unwrap()), (5.5, 0.5));
+        assert_eq!(grid.centre(grid.cell_at(3, 2, 2).unwrap()), (3.5, 6.5));
+
+        // Stairs are one step however far apart the panels are drawn
+        let (low, high) = (
+            grid.cell_at(2, 1, 0).unwrap(),
+            grid.cell_at(2, 1, 1).unwrap(),
+        );
+        assert_eq!(grid.distance(low, high), 1.0);
+    }
+    #[test]
+    fn test_level_mazes_use_stairs() {
+        let mut rng = StdRng::from_seed(&[7usize][..]);
+
+        for &algorithm in &[Algorithm::Dfs, Algorithm::Kruskal] {
+            let maze = generate_grid(LevelGrid::new(6, 5, 3), algorithm, 0.0, &mut rng);
+
+            // Levels are only joined by stairs, so some must have been used
+            let stairs = (0..maze.grid.cell_count())
+                .filter(|&cell| {
+                    maze.grid
+                        .stairs(cell)
+                        .iter()
+                        .any(|&(next, stair)| stair == Stair::Up && maze.is_linked(cell, next))
+                })
+                .count();
+            assert!(stairs > 0, "{} never went up a level", algorithm.name());
+        }
+    }
+}
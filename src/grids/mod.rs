@@ -0,0 +1,274 @@
+mod draw;
+mod hex;
+mod levels;
+mod polar;
+mod square;
+mod triangle;
+mod weave;
+
+use std::collections::HashSet;
+
+pub use self::draw::{generate_grid_image, generate_grid_svg};
+pub use self::hex::HexGrid;
+pub use self::levels::LevelGrid;
+pub use self::polar::PolarGrid;
+pub use self::square::{Direction, SquareGrid};
+pub use self::triangle::TriangleGrid;
+pub use self::weave::WeaveGrid;
+
+/// A position on the drawing of a grid, measured in cells from the top left.
+pub type Point = (f64, f64);
+
+// Longest straight piece used when drawing a curved edge, in cells
+const CURVE_STEP: f64 = 0.125;
+
+/// One side of a cell on the drawing of a grid.
+#[derive(Clone, Copy, Debug)]
+pub enum Edge {
+    Line(Point, Point),
+    /// Part of a circle, from one angle to another. Angles are in radians
+    /// and go clockwise, as y points down the drawing.
+    Arc {
+        centre: Point,
+        radius: f64,
+        from: f64,
+        to: f64,
+    },
+}
+
+impl Edge {
+    pub fn start(&self) -> Point {
+        match *self {
+            Edge::Line(from, _) => from,
+            Edge::Arc {
+                centre,
+                radius,
+                from,
+                ..
+            } => on_circle(centre, radius, from),
+        }
+    }
+
+    pub fn end(&self) -> Point {
+        match *self {
+            Edge::Line(_, to) => to,
+            Edge::Arc {
+                centre, radius, to, ..
+            } => on_circle(centre, radius, to),
+        }
+    }
+
+    /// Points along the edge from start to end, close enough together that
+    /// straight lines between them look like the edge.
+    pub fn points(&self) -> Vec<Point> {
+        match *self {
+            Edge::Line(from, to) => vec![from, to],
+            Edge::Arc {
+                centre,
+                radius,
+                from,
+                to,
+            } => {
+                let steps = ((to - from).abs() * radius / CURVE_STEP).ceil().max(1.0) as u32;
+
+                (0..=steps)
+                    .map(|step| {
+                        let angle = from + (to - from) * f64::from(step) / f64::from(steps);
+                        on_circle(centre, radius, angle)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Which way a flight of stairs goes from the cell it starts in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stair {
+    Up,
+    Down,
+}
+
+fn on_circle(centre: Point, radius: f64, angle: f64) -> Point {
+    (
+        centre.0 + radius * angle.cos(),
+        centre.1 + radius * angle.sin(),
+    )
+}
+
+/// The shape of the cells of a maze and how they sit next to each other.
+///
+/// Cells are numbered from zero, and every side of a cell is either shared
+/// with one neighbour or is on the outside edge of the grid.
+pub trait Grid {
+    /// Number of cells in the grid.
+    fn cell_count(&self) -> usize;
+
+    /// Each side of the cell in order clockwise round it, along with the cell
+    /// on the other side if there is one.
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Edge)>;
+
+    /// Middle of the cell, where solutions are drawn through.
+    fn centre(&self, cell: usize) -> Point;
+
+    /// Width and height of the drawing of the whole grid.
+    fn bounds(&self) -> Point;
+
+    /// The cell one step away in the given direction. Only grids laid out
+    /// in squares have compass directions, so on others it is never anywhere.
+    fn step(&self, _cell: usize, _direction: Direction) -> Option<usize> {
+        None
+    }
+
+    /// Cells joined to this one without sharing a side, such as the same
+    /// spot on the levels above and below. Most grids have none.
+    fn stairs(&self, _cell: usize) -> Vec<(usize, Stair)> {
+        Vec::new()
+    }
+
+    /// Ways to tunnel under a neighbour and come up in the cell straight on
+    /// from it, as the neighbour passed under and the cell beyond. Most grids
+    /// have none.
+    fn tunnels(&self, _cell: usize) -> Vec<(usize, usize)> {
+        Vec::new()
+    }
+
+    /// Pairs of cells either side of the given one that a tunnel under it
+    /// could join, each pair listed once.
+    fn crossings(&self, cell: usize) -> Vec<(usize, usize)> {
+        self.neighbours(cell)
+            .into_iter()
+            .flat_map(|neighbour| {
+                self.tunnels(neighbour)
+                    .into_iter()
+                    .filter(move |&(under, beyond)| under == cell && neighbour < beyond)
+                    .map(move |(_, beyond)| (neighbour, beyond))
+            })
+            .collect()
+    }
+
+    /// Cells sharing a side with the given one, then any reached by stairs.
+    fn neighbours(&self, cell: usize) -> Vec<usize> {
+        self.sides(cell)
+            .into_iter()
+            .filter_map(|(neighbour, _)| neighbour)
+            .chain(
+                self.stairs(cell)
+                    .into_iter()
+                    .map(|(neighbour, _)| neighbour),
+            )
+            .collect()
+    }
+
+    /// Straight line distance between the middles of two cells.
+    fn distance(&self, a: usize, b: usize) -> f64 {
+        let (from, to) = (self.centre(a), self.centre(b));
+        ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt()
+    }
+
+    /// Where solving starts, the first cell unless the grid says otherwise.
+    fn start(&self) -> usize {
+        0
+    }
+
+    /// Where solving ends, the last cell unless the grid says otherwise.
+    fn end(&self) -> usize {
+        self.cell_count() - 1
+    }
+}
+
+/// A maze on any grid, stored as the pairs of neighbouring cells that have a
+/// passage between them.
+pub struct GridMaze<G> {
+    pub grid: G,
+    links: HashSet<(usize, usize)>,
+}
+
+impl<G: Grid> GridMaze<G> {
+    /// A maze with every wall still standing.
+    pub fn new(grid: G) -> GridMaze<G> {
+        GridMaze {
+            grid,
+            links: HashSet::new(),
+        }
+    }
+
+    /// Knocks down the wall between two neighbouring cells.
+    pub fn link(&mut self, a: usize, b: usize) {
+        self.links.insert(pair(a, b));
+    }
+
+    pub fn is_linked(&self, a: usize, b: usize) -> bool {
+        self.links.contains(&pair(a, b))
+    }
+
+    /// Number of walls that have been knocked down.
+    pub fn link_count(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Neighbouring cells that can be walked to from the given one, then any
+    /// cells reached by tunnelling under a neighbour.
+    pub fn passages(&self, cell: usize) -> Vec<usize> {
+        self.grid
+            .neighbours(cell)
+            .into_iter()
+            .chain(
+                self.grid
+                    .tunnels(cell)
+                    .into_iter()
+                    .map(|(_, beyond)| beyond),
+            )
+            .filter(|&neighbour| self.is_linked(cell, neighbour))
+            .collect()
+    }
+
+    /// Whether a tunnel runs under the cell, so that it is a bridge with a
+    /// passage over it that can't be turned off of.
+    pub fn is_crossing(&self, cell: usize) -> bool {
+        self.grid
+            .crossings(cell)
+            .into_iter()
+            .any(|(a, b)| self.is_linked(a, b))
+    }
+}
+
+/// Links are stored smallest cell first so either order finds them.
+pub fn pair(a: usize, b: usize) -> (usize, usize) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links_either_way() {
+        let mut maze = GridMaze::new(HexGrid::new(3, 3));
+        let neighbour = maze.grid.neighbours(4)[0];
+
+        maze.link(neighbour, 4);
+        assert!(maze.is_linked(4, neighbour));
+        assert_eq!(maze.passages(4), vec![neighbour]);
+        assert_eq!(maze.passages(neighbour), vec![4]);
+
+        // Linking again doesn't knock down a second wall
+        maze.link(4, neighbour);
+        assert_eq!(maze.link_count(), 1);
+    }
+
+    #[test]
+    fn test_grids_never_empty() {
+        // Sizes of zero would leave no start or end, so they get one cell
+        assert_eq!(HexGrid::new(0, 0).cell_count(), 1);
+        assert_eq!(PolarGrid::new(0).cell_count(), 1);
+        assert_eq!(TriangleGrid::new(0, 3).cell_count(), 3);
+        assert_eq!(LevelGrid::new(2, 0, 0).cell_count(), 2);
+        assert_eq!(WeaveGrid::new(4, 0).cell_count(), 4);
+        assert_eq!(SquareGrid::new(0, 0).cell_count(), 1);
+    }
+}
@@ -0,0 +1,240 @@
+use std::f64::consts::PI;
+
+use super::{Edge, Grid, Point};
+
+/// A circular grid of rings around a single cell in the middle.
+///
+/// Each ring is one cell deep, and a ring is split into twice as many cells
+/// as the one inside it whenever its cells would otherwise get too wide.
+/// Cells are numbered ring by ring from the middle out, clockwise round each
+/// ring from the right hand side.
+pub struct PolarGrid {
+    rings: u32,
+    // Number of cells in each ring, and the number of the first one
+    counts: Vec<usize>,
+    firsts: Vec<usize>,
+}
+
+impl PolarGrid {
+    pub fn new(rings: u32) -> PolarGrid {
+        let rings = rings.max(1);
+        let mut counts = vec![1];
+        let mut firsts = vec![0];
+
+        for ring in 1..rings as usize {
+            let previous = counts[ring - 1];
+
+            // How many cells one ring depth wide would fit in each cell of
+            // the ring inside, going by the length round the inner edge
+            let width = 2.0 * PI * ring as f64 / previous as f64;
+            let ratio = (width.round() as usize).max(1);
+
+            firsts.push(firsts[ring - 1] + previous);
+            counts.push(previous * ratio);
+        }
+
+        PolarGrid {
+            rings,
+            counts,
+            firsts,
+        }
+    }
+
+    pub fn rings(&self) -> u32 {
+        self.rings
+    }
+
+    /// Number of cells in the given ring.
+    pub fn ring_size(&self, ring: u32) -> usize {
+        self.counts[ring as usize]
+    }
+
+    // Which ring a cell is in and how far round it is
+    fn locate(&self, cell: usize) -> (usize, usize) {
+        let ring = match self.firsts.binary_search(&cell) {
+            Ok(ring) => ring,
+            Err(next) => next - 1,
+        };
+
+        (ring, cell - self.firsts[ring])
+    }
+
+    fn cell(&self, ring: usize, index: usize) -> usize {
+        self.firsts[ring] + index % self.counts[ring]
+    }
+
+    fn middle(&self) -> Point {
+        (f64::from(self.rings), f64::from(self.rings))
+    }
+
+    // Angles that a cell starts and ends at
+    fn angles(&self, ring: usize, index: usize) -> (f64, f64) {
+        let step = 2.0 * PI / self.counts[ring] as f64;
+        (step * index as f64, step * (index + 1) as f64)
+    }
+
+    fn arc(&self, radius: usize, from: f64, to: f64) -> Edge {
+        Edge::Arc {
+            centre: self.middle(),
+            radius: radius as f64,
+            from,
+            to,
+        }
+    }
+
+    fn spoke(&self, angle: f64, from: usize, to: usize) -> Edge {
+        let (x, y) = self.middle();
+        let point = |radius: usize| {
+            (
+                x + radius as f64 * angle.cos(),
+                y + radius as f64 * angle.sin(),
+            )
+        };
+
+        Edge::Line(point(from), point(to))
+    }
+}
+
+impl Grid for PolarGrid {
+    fn cell_count(&self) -> usize {
+        self.firsts[self.firsts.len() - 1] + self.counts[self.counts.len() - 1]
+    }
+
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Edge)> {
+        let (ring, index) = self.locate(cell);
+        let outer = ring + 1 < self.counts.len();
+
+        // The middle cell is surrounded by the first ring, or by nothing if
+        // there is only the one cell
+        if ring == 0 {
+            if !outer {
+                return vec![
+                    (None, self.arc(1, 0.0, PI)),
+                    (None, self.arc(1, PI, 2.0 * PI)),
+                ];
+            }
+
+            return (0..self.counts[1])
+                .map(|next| {
+                    let (from, to) = self.angles(1, next);
+                    (Some(self.cell(1, next)), self.arc(1, from, to))
+                })
+                .collect();
+        }
+
+        let (start, end) = self.angles(ring, index);
+        let mut sides = Vec::new();
+
+        // Clockwise round the cell is out along the spoke it starts at,
+        // round the outer edge, back in along the other spoke and round the
+        // inner edge backwards
+        let before = self.cell(ring, index + self.counts[ring] - 1);
+        sides.push((Some(before), self.spoke(start, ring, ring + 1)));
+
+        if outer {
+            let ratio = self.counts[ring + 1] / self.counts[ring];
+            for next in index * ratio..(index + 1) * ratio {
+                let (from, to) = self.angles(ring + 1, next);
+                sides.push((
+                    Some(self.cell(ring + 1, next)),
+                    self.arc(ring + 1, from, to),
+                ));
+            }
+        } else {
+            sides.push((None, self.arc(ring + 1, start, end)));
+        }
+
+        let after = self.cell(ring, index + 1);
+        sides.push((Some(after), self.spoke(end, ring + 1, ring)));
+
+        let ratio = self.counts[ring] / self.counts[ring - 1];
+        let inward = self.cell(ring - 1, index / ratio);
+        sides.push((Some(inward), self.arc(ring, end, start)));
+
+        sides
+    }
+
+    fn centre(&self, cell: usize) -> Point {
+        let (ring, index) = self.locate(cell);
+        if ring == 0 {
+            return self.middle();
+        }
+
+        let (start, end) = self.angles(ring, index);
+        let (angle, radius) = ((start + end) / 2.0, ring as f64 + 0.5);
+        let (x, y) = self.middle();
+
+        (x + radius * angle.cos(), y + radius * angle.sin())
+    }
+
+    fn bounds(&self) -> Point {
+        (2.0 * f64::from(self.rings), 2.0 * f64::from(self.rings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Point, b: Point) -> bool {
+        (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_rings_subdivide() {
+        let grid = PolarGrid::new(8);
+        let counts: Vec<usize> = (0..8).map(|ring| grid.ring_size(ring)).collect();
+
+        assert_eq!(counts, vec![1, 6, 12, 24, 24, 24, 48, 48]);
+        assert_eq!(grid.cell_count(), counts.iter().sum::<usize>());
+        assert_eq!(grid.locate(7), (2, 0));
+        assert_eq!(grid.locate(grid.cell_count() - 1), (7, 47));
+    }
+
+    #[test]
+    fn test_polar_neighbours() {
+        let grid = PolarGrid::new(4);
+
+        // The middle touches every cell of the first ring
+        assert_eq!(grid.neighbours(0), vec![1, 2, 3, 4, 5, 6]);
+
+        // The first cell of the first ring has two cells outside it, as
+        // the second ring is split in two
+        let mut neighbours = grid.neighbours(1);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![0, 2, 6, 7, 8]);
+
+        // Cells wrap round at the end of a ring
+        assert!(grid.neighbours(18).contains(&7));
+    }
+
+    #[test]
+    fn test_polar_sides_are_shared() {
+        let grid = PolarGrid::new(6);
+
+        for cell in 0..grid.cell_count() {
+            let sides = grid.sides(cell);
+
+            // Each side carries on from where the last one stopped
+            for (index, &(_, edge)) in sides.iter().enumerate() {
+                let (_, next) = sides[(index + 1) % sides.len()];
+                assert!(close(edge.end(), next.start()), "cell {} has a gap", cell);
+            }
+
+            // Both cells either side of a wall draw it in the same place
+            for (neighbour, edge) in sides {
+                let neighbour = match neighbour {
+                    Some(neighbour) => neighbour,
+                    None => continue,
+                };
+
+                let matching = grid.sides(neighbour).into_iter().any(|(back, other)| {
+                    back == Some(cell)
+                        && close(other.start(), edge.end())
+                        && close(other.end(), edge.start())
+                });
+                assert!(matching, "cells {} and {} disagree", cell, neighbour);
+            }
+        }
+    }
+}
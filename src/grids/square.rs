@@ -0,0 +1,178 @@
+use mazebuilder::Mask;
+
+use super::{Edge, Grid, Point};
+
+/// Ways out of a cell of a square grid. Up and down take the stairs to the
+/// same spot on the level above or below, so they only lead anywhere on a
+/// `LevelGrid`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+    Up,
+    Down,
+}
+
+impl Direction {
+    /// The four ways out across a single level.
+    pub const FLAT: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub const ALL: [Direction; 6] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+        Direction::Up,
+        Direction::Down,
+    ];
+
+    // How far a step goes in columns, rows and levels
+    pub(super) fn offset(&self) -> (i64, i64, i64) {
+        match *self {
+            Direction::North => (0, -1, 0),
+            Direction::East => (1, 0, 0),
+            Direction::South => (0, 1, 0),
+            Direction::West => (-1, 0, 0),
+            Direction::Up => (0, 0, 1),
+            Direction::Down => (0, 0, -1),
+        }
+    }
+}
+
+/// A grid of squares, optionally cut down to the cells inside a mask.
+///
+/// Cells are counted in columns and rows, so cell `(column, row)` is number
+/// `row * columns + column`. Cells outside the mask are still numbered but
+/// have no neighbours, so nothing is ever carved into them.
+pub struct SquareGrid {
+    columns: u32,
+    rows: u32,
+    mask: Option<Mask>,
+}
+
+impl SquareGrid {
+    pub fn new(columns: u32, rows: u32) -> SquareGrid {
+        SquareGrid {
+            columns: columns.max(1),
+            rows: rows.max(1),
+            mask: None,
+        }
+    }
+
+    /// A grid the size of the mask, with only the cells inside it joined up.
+    pub fn masked(mask: &Mask) -> SquareGrid {
+        SquareGrid {
+            columns: mask.columns().max(1),
+            rows: mask.rows().max(1),
+            mask: Some(mask.clone()),
+        }
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Column and row of a cell.
+    pub fn position(&self, cell: usize) -> (u32, u32) {
+        let columns = self.columns as usize;
+        ((cell % columns) as u32, (cell / columns) as u32)
+    }
+
+    pub fn cell_at(&self, column: u32, row: u32) -> Option<usize> {
+        if column >= self.columns || row >= self.rows {
+            return None;
+        }
+
+        Some((row * self.columns + column) as usize)
+    }
+
+    /// Whether the cell is part of the maze rather than cut out by the mask.
+    pub fn is_inside(&self, cell: usize) -> bool {
+        let (column, row) = self.position(cell);
+        self.mask
+            .as_ref()
+            .is_none_or(|mask| mask.is_inside(column, row))
+    }
+}
+
+impl Grid for SquareGrid {
+    fn cell_count(&self) -> usize {
+        (self.columns * self.rows) as usize
+    }
+
+    /// The cell one step away in the given direction, if it is on the grid
+    /// and both cells are inside the mask. There is only one level, so up and
+    /// down never lead anywhere.
+    fn step(&self, cell: usize, direction: Direction) -> Option<usize> {
+        let (column, row) = self.position(cell);
+        let (dx, dy, dz) = direction.offset();
+        let (x, y) = (i64::from(column) + dx, i64::from(row) + dy);
+
+        if x < 0 || y < 0 || dz != 0 || !self.is_inside(cell) {
+            return None;
+        }
+
+        self.cell_at(x as u32, y as u32)
+            .filter(|&next| self.is_inside(next))
+    }
+
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Edge)> {
+        let (column, row) = self.position(cell);
+        let (x, y) = (f64::from(column), f64::from(row));
+        let corners = [(x, y), (x + 1.0, y), (x + 1.0, y + 1.0), (x, y + 1.0)];
+
+        Direction::FLAT
+            .iter()
+            .enumerate()
+            .map(|(side, &direction)| {
+                let edge = Edge::Line(corners[side], corners[(side + 1) % 4]);
+                (self.step(cell, direction), edge)
+            })
+            .collect()
+    }
+
+    fn centre(&self, cell: usize) -> Point {
+        let (column, row) = self.position(cell);
+        (f64::from(column) + 0.5, f64::from(row) + 0.5)
+    }
+
+    fn bounds(&self) -> Point {
+        (f64::from(self.columns), f64::from(self.rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_steps() {
+        let grid = SquareGrid::new(4, 3);
+
+        assert_eq!(grid.position(6), (2, 1));
+        assert_eq!(grid.step(6, Direction::North), Some(2));
+        assert_eq!(grid.step(6, Direction::West), Some(5));
+        assert_eq!(grid.step(0, Direction::North), None);
+        assert_eq!(grid.step(11, Direction::East), None);
+        assert_eq!(grid.step(6, Direction::Up), None);
+
+        // The mask cuts cells off from their neighbours both ways
+        let mut mask = Mask::new(4, 3);
+        mask.set(2, 1, false);
+        let grid = SquareGrid::masked(&mask);
+
+        assert!(grid.neighbours(6).is_empty());
+        assert_eq!(grid.neighbours(2), vec![3, 1]);
+    }
+}
@@ -0,0 +1,166 @@
+use super::{Edge, Grid, Point};
+
+// Height of a triangle with sides one cell long
+const TRIANGLE_HEIGHT: f64 = 0.866_025_403_784_438_6;
+
+/// A grid of triangles that take turns pointing up and down, each with up
+/// to three neighbours.
+///
+/// Cells are counted in columns and rows, so cell `(column, row)` is number
+/// `row * columns + column`. The top left triangle points up, and each one
+/// overlaps the one before it by half its width.
+pub struct TriangleGrid {
+    columns: u32,
+    rows: u32,
+}
+
+impl TriangleGrid {
+    pub fn new(columns: u32, rows: u32) -> TriangleGrid {
+        TriangleGrid {
+            columns: columns.max(1),
+            rows: rows.max(1),
+        }
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Whether the cell points up, with its flat side along the bottom.
+    pub fn points_up(&self, cell: usize) -> bool {
+        let (column, row) = self.position(cell);
+        (column + row) % 2 == 0
+    }
+
+    fn position(&self, cell: usize) -> (i64, i64) {
+        let columns = self.columns as usize;
+        ((cell % columns) as i64, (cell / columns) as i64)
+    }
+
+    fn cell(&self, column: i64, row: i64) -> Option<usize> {
+        if column < 0 || row < 0 || column >= i64::from(self.columns) || row >= i64::from(self.rows)
+        {
+            return None;
+        }
+
+        Some((row * i64::from(self.columns) + column) as usize)
+    }
+}
+
+impl Grid for TriangleGrid {
+    fn cell_count(&self) -> usize {
+        (self.columns * self.rows) as usize
+    }
+
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Edge)> {
+        let (column, row) = self.position(cell);
+        let left = column as f64 / 2.0;
+        let (middle, right) = (left + 0.5, left + 1.0);
+        let top = row as f64 * TRIANGLE_HEIGHT;
+        let bottom = top + TRIANGLE_HEIGHT;
+
+        // Clockwise from the bottom left corner of an upwards triangle, or
+        // the top left corner of a downwards one
+        if self.points_up(cell) {
+            vec![
+                (
+                    self.cell(column - 1, row),
+                    Edge::Line((left, bottom), (middle, top)),
+                ),
+                (
+                    self.cell(column + 1, row),
+                    Edge::Line((middle, top), (right, bottom)),
+                ),
+                (
+                    self.cell(column, row + 1),
+                    Edge::Line((right, bottom), (left, bottom)),
+                ),
+            ]
+        } else {
+            vec![
+                (
+                    self.cell(column, row - 1),
+                    Edge::Line((left, top), (right, top)),
+                ),
+                (
+                    self.cell(column + 1, row),
+                    Edge::Line((right, top), (middle, bottom)),
+                ),
+                (
+                    self.cell(column - 1, row),
+                    Edge::Line((middle, bottom), (left, top)),
+                ),
+            ]
+        }
+    }
+
+    fn centre(&self, cell: usize) -> Point {
+        let (column, row) = self.position(cell);
+
+        // The middle of a triangle is a third of the way up from its flat side
+        let depth = if self.points_up(cell) { 2.0 } else { 1.0 };
+
+        (
+            (column + 1) as f64 / 2.0,
+            (row as f64 + depth / 3.0) * TRIANGLE_HEIGHT,
+        )
+    }
+
+    fn bounds(&self) -> Point {
+        (
+            (f64::from(self.columns) + 1.0) / 2.0,
+            f64::from(self.rows) * TRIANGLE_HEIGHT,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangle_neighbours() {
+        let grid = TriangleGrid::new(5, 3);
+
+        // (1, 1) points up, so it joins the row below
+        assert!(grid.points_up(6));
+        let mut neighbours = grid.neighbours(6);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![5, 7, 11]);
+
+        // (2, 1) points down, so it joins the row above
+        assert!(!grid.points_up(7));
+        let mut neighbours = grid.neighbours(7);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![2, 6, 8]);
+
+        // The top left corner has one beside it and one below
+        assert_eq!(grid.neighbours(0), vec![1, 5]);
+    }
+
+    #[test]
+    fn test_triangle_sides_are_shared() {
+        let grid = TriangleGrid::new(6, 4);
+        let close = |a: Point, b: Point| (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9;
+
+        for cell in 0..grid.cell_count() {
+            for (neighbour, edge) in grid.sides(cell) {
+                let neighbour = match neighbour {
+                    Some(neighbour) => neighbour,
+                    None => continue,
+                };
+
+                let matching = grid.sides(neighbour).into_iter().any(|(back, other)| {
+                    back == Some(cell)
+                        && close(other.start(), edge.end())
+                        && close(other.end(), edge.start())
+                });
+                assert!(matching, "cells {} and {} disagree", cell, neighbour);
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use super::{Direction, Edge, Grid, Point, SquareGrid};
+
+/// A square grid where passages can tunnel under the cell next to them and
+/// come up in the one beyond, as in a weave maze.
+///
+/// Cells are numbered just like a `SquareGrid`, which lays out the cells
+/// underneath. A cell with a tunnel under it keeps its own passage straight
+/// across the other way, like a bridge.
+pub struct WeaveGrid {
+    squares: SquareGrid,
+}
+
+impl WeaveGrid {
+    pub fn new(columns: u32, rows: u32) -> WeaveGrid {
+        WeaveGrid {
+            squares: SquareGrid::new(columns, rows),
+        }
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.squares.columns()
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.squares.rows()
+    }
+}
+
+impl Grid for WeaveGrid {
+    fn cell_count(&self) -> usize {
+        self.squares.cell_count()
+    }
+
+    fn step(&self, cell: usize, direction: Direction) -> Option<usize> {
+        self.squares.step(cell, direction)
+    }
+
+    fn sides(&self, cell: usize) -> Vec<(Option<usize>, Edge)> {
+        self.squares.sides(cell)
+    }
+
+    fn tunnels(&self, cell: usize) -> Vec<(usize, usize)> {
+        Direction::FLAT
+            .iter()
+            .filter_map(|&direction| {
+                let under = self.step(cell, direction)?;
+                let beyond = self.step(under, direction)?;
+                Some((under, beyond))
+            })
+            .collect()
+    }
+
+    fn centre(&self, cell: usize) -> Point {
+        self.squares.centre(cell)
+    }
+
+    fn bounds(&self) -> Point {
+        self.squares.bounds()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grids::pair;
+    use mazebuilder::{generate_grid, Algorithm};
+    use rand::{SeedableRng, StdRng};
+
+    #[test]
+    fn test_weave_tunnels() {
+        let grid = WeaveGrid::new(4, 3);
+
+        // The top left corner can only tunnel right or down
+        assert_eq!(grid.tunnels(0), vec![(1, 2), (4, 8)]);
+
+        // (1, 1) has room to come up again only to the right
+        assert_eq!(grid.tunnels(5), vec![(6, 7)]);
+
+        // Tunnels are only ever a way on, never a neighbour
+        let mut neighbours = grid.neighbours(5);
+        neighbours.sort();
+        assert_eq!(neighbours, vec![1, 4, 6, 9]);
+
+        // A tunnel under (1, 1) could go either way across it
+        assert_eq!(grid.crossings(5), vec![(1, 9), (4, 6)]);
+    }
+    #[test]
+    fn test_weave_bridges_are_straight() {
+        let mut rng = StdRng::from_seed(&[7usize][..]);
+
+        for &algorithm in &[Algorithm::Dfs, Algorithm::Kruskal] {
+            let maze = generate_grid(WeaveGrid::new(12, 10), algorithm, 0.0, &mut rng);
+
+            // Every bridge is a straight passage that nothing turns off of
+            let bridges: Vec<usize> = (0..maze.grid.cell_count())
+                .filter(|&cell| maze.is_crossing(cell))
+                .collect();
+            assert!(
+                !bridges.is_empty(),
+                "{} made no crossings",
+                algorithm.name()
+            );
+
+            for cell in bridges {
+                let passages = maze.passages(cell);
+                assert_eq!(passages.len(), 2);
+                assert!(maze
+                    .grid
+                    .crossings(cell)
+                    .contains(&pair(passages[0], passages[1])));
+            }
+        }
+    }
+}
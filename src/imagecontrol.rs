@@ -1,39 +1,361 @@
 extern crate image;
 
-use std::collections::HashSet; 
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::io;
 
-pub fn generate_image(height: u32, width: u32, node_map: &HashSet<(u32, u32)>) {
+/// File formats a maze image can be saved as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Bmp,
+    Ppm,
+    Gif,
+}
+
+impl ImageFormat {
+    /// File extension images in this format are saved with.
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Gif => "gif",
+        }
+    }
+
+    /// Picks the format from the extension at the end of a file name.
+    pub fn from_filename(filename: &str) -> Option<ImageFormat> {
+        let extension = filename.rsplit('.').next()?.to_lowercase();
+
+        match extension.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "bmp" => Some(ImageFormat::Bmp),
+            "ppm" => Some(ImageFormat::Ppm),
+            "gif" => Some(ImageFormat::Gif),
+            _ => None,
+        }
+    }
+
+    // The image crate only knows how to write PPM under its old name
+    #[allow(deprecated)]
+    fn encoding(self) -> image::ImageFormat {
+        match self {
+            ImageFormat::Png => image::PNG,
+            ImageFormat::Jpeg => image::JPEG,
+            ImageFormat::Bmp => image::BMP,
+            ImageFormat::Ppm => image::PPM,
+            ImageFormat::Gif => image::GIF,
+        }
+    }
+}
+
+/// A maze drawn in memory, one pixel per passage.
+pub type MazeImage = image::RgbImage;
+
+/// A line to draw onto an image, in pixels, with its colour.
+pub type Stroke = ((f32, f32), (f32, f32), [u8; 3]);
+
+// Images saved without render options keep one image pixel per maze pixel
+const SAVED_BLOCK: u32 = 1;
+
+pub fn generate_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    let options = RenderOptions::blocks(SAVED_BLOCK);
+    let img_buffer = render_image(height, width, node_map, None, &options);
+
+    // Send the image
+    save_image(image::ImageRgb8(img_buffer), filename, format)
+}
+
+pub fn generate_solved_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    path: Vec<&(u32, u32)>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    let options = RenderOptions::blocks(SAVED_BLOCK);
+    let img_buffer = render_image(height, width, node_map, Some(path), &options);
+
+    // Send the image
+    save_image(image::ImageRgb8(img_buffer), filename, format)
+}
+
+pub fn solved_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    path: &[&(u32, u32)],
+) -> MazeImage {
     // Create image buffer
     let mut img_buffer = image::ImageBuffer::new(width, height);
 
     // Iterate over the coords and pixels in the image
     for (x, y, pixel) in img_buffer.enumerate_pixels_mut() {
-        let position = (x as u32, y as u32);
-        let node = node_map.get(&position);
+        let position = (x, y);
 
-        if node.is_none() {
-            *pixel = image::Luma([0u8]);
+        if !node_map.contains(&position) {
+            *pixel = image::Rgb([0u8, 0, 0]);
         } else {
-            *pixel = image::Luma([255u8])
+            *pixel = image::Rgb([255u8, 255, 255])
+        }
+    }
+
+    // index / length of array, color * result
+    for (index, pos) in path.iter().enumerate() {
+        let position = (pos.0, pos.1);
+        let pixel = img_buffer.get_pixel_mut(position.0, position.1);
+
+        let percent = index as f32 / path.len() as f32;
+        let color_b = (255f32 * percent) as u8;
+        let color_r = 255 - color_b;
+
+        *pixel = image::Rgb([color_r, 0, color_b]);
+    }
+
+    img_buffer
+}
+
+/// Encodes an image into the bytes of an image file in the given format.
+pub fn encode_image(img: MazeImage, format: ImageFormat) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    image::ImageRgb8(img)
+        .save(&mut bytes, format.encoding())
+        .map_err(to_io_error)?;
+
+    Ok(bytes)
+}
+
+pub fn save_image(img: image::DynamicImage, filename: &str, format: ImageFormat) -> io::Result<()> {
+    let fout = &mut File::create(filename)?;
+    img.save(fout, format.encoding()).map_err(to_io_error)
+}
+
+// Keeps any underlying IO error as it is, describing anything else
+fn to_io_error(err: image::ImageError) -> io::Error {
+    match err {
+        image::ImageError::IoError(err) => err,
+        err => io::Error::other(err.to_string()),
+    }
+}
+
+pub fn generate_marked_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    path: Vec<&(u32, u32)>,
+    marks: &HashMap<&(u32, u32), u8>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    // Passages marked once are tinted, passages marked twice are greyed out
+    generate_shaded_image(
+        height,
+        width,
+        node_map,
+        path,
+        filename,
+        format,
+        |position| match marks.get(position) {
+            Some(1) => Some(image::Rgb([255u8, 230, 150])),
+            Some(_) => Some(image::Rgb([170u8, 170, 170])),
+            None => None,
+        },
+    )
+}
+
+pub fn generate_filled_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    path: Vec<&(u32, u32)>,
+    filled: &HashSet<&(u32, u32)>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    // Grey out everything that was filled in as a dead end
+    generate_shaded_image(
+        height,
+        width,
+        node_map,
+        path,
+        filename,
+        format,
+        |position| {
+            if filled.contains(position) {
+                Some(image::Rgb([170u8, 170, 170]))
+            } else {
+                None
+            }
+        },
+    )
+}
+
+/// How a heatmap should colour the nodes a solver looked at.
+pub enum Heat {
+    /// Earlier expansions are lighter than later ones
+    Order,
+    /// Nodes stepped on more often are darker
+    Visits,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn generate_heatmap_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    path: Vec<&(u32, u32)>,
+    trace: &[(u32, u32)],
+    heat: Heat,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    // Work out how hot each node is, between 0 and 1
+    let mut levels: HashMap<(u32, u32), f32> = HashMap::new();
+
+    match heat {
+        Heat::Order => {
+            for (index, node) in trace.iter().enumerate() {
+                let level = (index + 1) as f32 / trace.len() as f32;
+                levels.entry(*node).or_insert(level);
+            }
+        }
+        Heat::Visits => {
+            for node in trace {
+                *levels.entry(*node).or_insert(0f32) += 1f32;
+            }
+
+            let most = levels.values().cloned().fold(1f32, f32::max);
+            for level in levels.values_mut() {
+                *level /= most;
+            }
+        }
+    }
+
+    // Fade from a pale green for cool nodes to a dark teal for hot ones
+    generate_shaded_image(
+        height,
+        width,
+        node_map,
+        path,
+        filename,
+        format,
+        |position| {
+            levels.get(position).map(|level| {
+                let r = 210f32 - 190f32 * level;
+                let g = 245f32 - 135f32 * level;
+                let b = 200f32 - 90f32 * level;
+
+                image::Rgb([r as u8, g as u8, b as u8])
+            })
+        },
+    )
+}
+
+/// Colour scales used to show how far each passage is from the start.
+pub enum Gradient {
+    /// Perceptually even scale from dark purple through teal to yellow
+    Viridis,
+    /// Every hue from red round to magenta
+    Rainbow,
+    /// A straight blend from the first colour to the second
+    TwoColour([u8; 3], [u8; 3]),
+}
+
+impl Gradient {
+    /// Picks the colour a given distance along the scale, between 0 and 1.
+    pub fn colour(&self, t: f32) -> image::Rgb<u8> {
+        let t = t.clamp(0f32, 1f32);
+
+        match *self {
+            Gradient::Viridis => {
+                let stops = [
+                    [68u8, 1, 84],
+                    [59, 82, 139],
+                    [33, 145, 140],
+                    [94, 201, 98],
+                    [253, 231, 37],
+                ];
+                let scaled = t * (stops.len() - 1) as f32;
+                let index = (scaled.floor() as usize).min(stops.len() - 2);
+
+                blend(stops[index], stops[index + 1], scaled - index as f32)
+            }
+            // Stop short of going all the way back round to red
+            Gradient::Rainbow => hue_to_rgb(t * 5f32 / 6f32),
+            Gradient::TwoColour(from, to) => blend(from, to, t),
+        }
+    }
+}
+
+// Mixes two colours, 0 gives all of the first and 1 all of the second
+pub fn blend(from: [u8; 3], to: [u8; 3], t: f32) -> image::Rgb<u8> {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+    image::Rgb([
+        mix(from[0], to[0]),
+        mix(from[1], to[1]),
+        mix(from[2], to[2]),
+    ])
+}
+
+pub fn generate_distance_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    distances: &HashMap<(u32, u32), u32>,
+    gradient: &Gradient,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    // Create image buffer
+    let mut img_buffer = image::ImageBuffer::new(width, height);
+
+    let furthest = distances.values().cloned().max().unwrap_or(0).max(1) as f32;
+
+    // Iterate over the coords and pixels in the image, colouring each passage
+    // by how far it is from the start. Anything that can't be reached is grey.
+    for (x, y, pixel) in img_buffer.enumerate_pixels_mut() {
+        let position = (x, y);
+
+        if !node_map.contains(&position) {
+            *pixel = image::Rgb([0u8, 0, 0]);
+        } else {
+            *pixel = match distances.get(&position) {
+                Some(distance) => gradient.colour(*distance as f32 / furthest),
+                None => image::Rgb([128u8, 128, 128]),
+            }
         }
     }
 
     // Send the image
-    let ref mut fout = File::create("maze.png").unwrap();
-    image::ImageLuma8(img_buffer)
-        .save(fout, image::PNG)
-        .unwrap();
+    save_image(image::ImageRgb8(img_buffer), filename, format)
 }
 
-pub fn generate_solved_image(height: u32, width: u32, node_map: &HashSet<(u32, u32)>, path: Vec<&(u32, u32)>) {
+pub fn generate_solutions_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    paths: &[Vec<&(u32, u32)>],
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
     // Create image buffer
     let mut img_buffer = image::ImageBuffer::new(width, height);
 
     // Iterate over the coords and pixels in the image
     for (x, y, pixel) in img_buffer.enumerate_pixels_mut() {
-        let position = (x as u32, y as u32);
-        //let node = node_map.get(&position);
+        let position = (x, y);
 
         if !node_map.contains(&position) {
             *pixel = image::Rgb([0u8, 0, 0]);
@@ -42,21 +364,519 @@ pub fn generate_solved_image(height: u32, width: u32, node_map: &HashSet<(u32, u
         }
     }
 
+    // Give each path its own colour, spread evenly around the colour wheel
+    for (index, path) in paths.iter().enumerate() {
+        let hue = index as f32 / paths.len() as f32;
+        let colour = hue_to_rgb(hue);
+
+        for pos in path {
+            *img_buffer.get_pixel_mut(pos.0, pos.1) = colour;
+        }
+    }
+
+    // Send the image
+    save_image(image::ImageRgb8(img_buffer), filename, format)
+}
+
+// Converts a hue between 0 and 1 into a fully saturated colour
+fn hue_to_rgb(hue: f32) -> image::Rgb<u8> {
+    let h = (hue.fract() * 6f32).floor() as u32;
+    let f = hue.fract() * 6f32 - h as f32;
+    let rising = (255f32 * f) as u8;
+    let falling = 255 - rising;
+
+    match h {
+        0 => image::Rgb([255u8, rising, 0]),
+        1 => image::Rgb([falling, 255u8, 0]),
+        2 => image::Rgb([0u8, 255, rising]),
+        3 => image::Rgb([0u8, falling, 255]),
+        4 => image::Rgb([rising, 0u8, 255]),
+        _ => image::Rgb([255u8, 0, falling]),
+    }
+}
+
+fn generate_shaded_image<F>(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    path: Vec<&(u32, u32)>,
+    filename: &str,
+    format: ImageFormat,
+    shade: F,
+) -> io::Result<()>
+where
+    F: Fn(&(u32, u32)) -> Option<image::Rgb<u8>>,
+{
+    // Create image buffer
+    let mut img_buffer = image::ImageBuffer::new(width, height);
+
+    // Iterate over the coords and pixels in the image, using the shade
+    // for any passage that has one.
+    for (x, y, pixel) in img_buffer.enumerate_pixels_mut() {
+        let position = (x, y);
+
+        if !node_map.contains(&position) {
+            *pixel = image::Rgb([0u8, 0, 0]);
+        } else {
+            *pixel = shade(&position).unwrap_or(image::Rgb([255u8, 255, 255]));
+        }
+    }
+
     // index / length of array, color * result
-    for (index, pos) in path.iter().enumerate(){
-        let position = (pos.0 as u32, pos.1 as u32);
-        let pixel = img_buffer.get_pixel_mut(position.0, position.1);
+    for (index, pos) in path.iter().enumerate() {
+        let pixel = img_buffer.get_pixel_mut(pos.0, pos.1);
 
         let percent = index as f32 / path.len() as f32;
-        let color_b = (255f32 * percent) as u8; 
+        let color_b = (255f32 * percent) as u8;
         let color_r = 255 - color_b;
 
         *pixel = image::Rgb([color_r, 0, color_b]);
     }
 
     // Send the image
-    let ref mut fout = File::create("solved-maze.png").unwrap();
-    image::ImageRgb8(img_buffer)
-        .save(fout, image::PNG)
-        .unwrap();
+    save_image(image::ImageRgb8(img_buffer), filename, format)
+}
+
+/// Colour schemes for rendered mazes.
+pub enum Theme {
+    /// Black walls on white, with the path fading from red to blue
+    Classic,
+    /// Light walls on a dark background
+    Dark,
+    /// White walls on blueprint blue
+    Blueprint,
+}
+
+/// Settings for drawing a maze bigger than one pixel per passage.
+#[derive(Clone)]
+pub struct RenderOptions {
+    /// Width of each passage, in pixels
+    pub passage_width: u32,
+    /// Thickness of each wall, in pixels
+    pub wall_thickness: u32,
+    /// Empty border left around the maze, in pixels
+    pub margin: u32,
+    pub wall_colour: [u8; 3],
+    pub passage_colour: [u8; 3],
+    /// Colour of the solution, or a fade from red to blue if there isn't one
+    pub path_colour: Option<[u8; 3]>,
+    /// Smooth the edges of the walls and the solution line
+    pub antialias: bool,
+    /// Colour of the margin and anywhere outside the maze, such as around
+    /// the shape of a masked maze. The passage colour is used if not set.
+    pub background: Option<[u8; 3]>,
+}
+
+impl RenderOptions {
+    pub fn new(theme: Theme) -> RenderOptions {
+        let (wall_colour, passage_colour, path_colour) = match theme {
+            Theme::Classic => ([0u8, 0, 0], [255u8, 255, 255], None),
+            Theme::Dark => ([200u8, 200, 210], [25u8, 25, 35], Some([255u8, 170, 0])),
+            Theme::Blueprint => ([240u8, 245, 255], [20u8, 60, 140], Some([255u8, 220, 60])),
+        };
+
+        RenderOptions {
+            passage_width: 8,
+            wall_thickness: 2,
+            margin: 8,
+            wall_colour,
+            passage_colour,
+            path_colour,
+            antialias: true,
+            background: None,
+        }
+    }
+
+    /// Plain squares of the classic colours, walls and passages all the same
+    /// size with no margin or smoothing, so the image loader can read the
+    /// maze back in.
+    pub fn blocks(size: u32) -> RenderOptions {
+        RenderOptions {
+            passage_width: size,
+            wall_thickness: size,
+            margin: 0,
+            antialias: false,
+            ..RenderOptions::new(Theme::Classic)
+        }
+    }
+
+    // Where a row or column of the maze starts in the image, and how wide it is.
+    // Even rows and columns are walls, odd ones are passages.
+    fn band(&self, position: u32) -> (u32, u32) {
+        let start = self.margin
+            + (position / 2) * (self.wall_thickness + self.passage_width)
+            + (position % 2) * self.wall_thickness;
+
+        if position.is_multiple_of(2) {
+            (start, self.wall_thickness)
+        } else {
+            (start, self.passage_width)
+        }
+    }
+
+    // Middle of a row or column of the maze in the image
+    fn centre(&self, position: u32) -> f32 {
+        let (start, size) = self.band(position);
+        start as f32 + size as f32 / 2f32
+    }
+
+    // Size of the image a maze of the given size is drawn into
+    fn image_size(&self, height: u32, width: u32) -> (u32, u32) {
+        let (x, w) = self.band(width - 1);
+        let (y, h) = self.band(height - 1);
+
+        (x + w + self.margin, y + h + self.margin)
+    }
+}
+
+pub fn generate_rendered_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    path: Option<Vec<&(u32, u32)>>,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    let img_buffer = render_image(height, width, node_map, path, options);
+
+    // Send the image
+    save_image(image::ImageRgb8(img_buffer), filename, format)
+}
+
+fn render_image(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<(u32, u32)>,
+    path: Option<Vec<&(u32, u32)>>,
+    options: &RenderOptions,
+) -> image::RgbImage {
+    let (image_width, image_height) = options.image_size(height, width);
+    let background = options.background.unwrap_or(options.passage_colour);
+    let mut img_buffer =
+        image::ImageBuffer::from_pixel(image_width, image_height, image::Rgb(background));
+
+    let is_wall = |x: u32, y: u32| {
+        x < width
+            && y < height
+            && !node_map.contains(&(x, y))
+            && !(options.background.is_some() && is_outside((x, y), node_map))
+    };
+
+    // Fill in the block covered by every pixel of the maze
+    let mut walls: Vec<Stroke> = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let colour = if node_map.contains(&(x, y)) {
+                options.passage_colour
+            } else if !is_wall(x, y) {
+                background
+            } else if options.antialias && (x % 2 == 0 || y % 2 == 0) {
+                // Smoothed walls are drawn over the passages as lines, so
+                // their ends and outside corners come out round
+                walls.extend(wall_strokes((x, y), &is_wall, options));
+                options.passage_colour
+            } else {
+                options.wall_colour
+            };
+
+            if colour == background {
+                continue;
+            }
+
+            let (left, columns) = options.band(x);
+            let (top, rows) = options.band(y);
+
+            for py in top..top + rows {
+                for px in left..left + columns {
+                    img_buffer.put_pixel(px, py, image::Rgb(colour));
+                }
+            }
+        }
+    }
+
+    let radius = options.wall_thickness as f32 / 2f32;
+    paint(&mut img_buffer, &walls, radius, options.antialias);
+
+    if let Some(path) = path {
+        draw_path(&mut img_buffer, &path, options);
+    }
+
+    img_buffer
+}
+
+// A line down the length of a wall pixel, and lines joining it to the middle
+// of the wall pixels to its right and below
+fn wall_strokes<F>(node: (u32, u32), is_wall: &F, options: &RenderOptions) -> Vec<Stroke>
+where
+    F: Fn(u32, u32) -> bool,
+{
+    let (x, y) = node;
+    let (left, columns) = options.band(x);
+    let (top, rows) = options.band(y);
+    let radius = options.wall_thickness as f32 / 2f32;
+    let colour = options.wall_colour;
+
+    let mut strokes = vec![(
+        (left as f32 + radius, top as f32 + radius),
+        (
+            (left + columns) as f32 - radius,
+            (top + rows) as f32 - radius,
+        ),
+        colour,
+    )];
+
+    let centre = (options.centre(x), options.centre(y));
+    if is_wall(x + 1, y) {
+        strokes.push((centre, (options.centre(x + 1), centre.1), colour));
+    }
+    if is_wall(x, y + 1) {
+        strokes.push((centre, (centre.0, options.centre(y + 1)), colour));
+    }
+
+    strokes
+}
+
+// Walls with no passage anywhere around them, even diagonally, aren't needed
+// to close off any passage, so they must be outside the maze
+fn is_outside(node: (u32, u32), node_map: &HashSet<(u32, u32)>) -> bool {
+    let (x, y) = (i64::from(node.0), i64::from(node.1));
+
+    for ny in y - 1..=y + 1 {
+        for nx in x - 1..=x + 1 {
+            if nx >= 0 && ny >= 0 && node_map.contains(&(nx as u32, ny as u32)) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+// Draws the path as a line through the middle of each passage, half as wide
+// as the passage itself
+fn draw_path(img_buffer: &mut image::RgbImage, path: &[&(u32, u32)], options: &RenderOptions) {
+    let strokes: Vec<Stroke> = path
+        .windows(2)
+        .enumerate()
+        .map(|(index, pair)| {
+            let colour = match options.path_colour {
+                Some(colour) => colour,
+                None => {
+                    let percent = index as f32 / path.len() as f32;
+                    let color_b = (255f32 * percent) as u8;
+                    [255 - color_b, 0, color_b]
+                }
+            };
+
+            (
+                (options.centre(pair[0].0), options.centre(pair[0].1)),
+                (options.centre(pair[1].0), options.centre(pair[1].1)),
+                colour,
+            )
+        })
+        .collect();
+
+    let radius = (options.passage_width as f32 / 4f32).max(0.5);
+    paint(img_buffer, &strokes, radius, options.antialias);
+}
+
+// Draws lines of the given half width. Where lines overlap the one covering
+// the most of a pixel wins, so joins stay smooth.
+pub fn paint(img_buffer: &mut image::RgbImage, strokes: &[Stroke], radius: f32, antialias: bool) {
+    let (image_width, image_height) = img_buffer.dimensions();
+    let mut coverage = vec![0f32; (image_width * image_height) as usize];
+    let mut colours = vec![[0u8; 3]; (image_width * image_height) as usize];
+
+    for &(from, to, colour) in strokes {
+        // Only look at pixels near the line
+        let left = (from.0.min(to.0) - radius - 1f32).max(0f32) as u32;
+        let right = ((from.0.max(to.0) + radius + 1f32) as u32).min(image_width);
+        let top = (from.1.min(to.1) - radius - 1f32).max(0f32) as u32;
+        let bottom = ((from.1.max(to.1) + radius + 1f32) as u32).min(image_height);
+
+        for py in top..bottom {
+            for px in left..right {
+                let distance = distance_to_segment((px as f32 + 0.5, py as f32 + 0.5), from, to);
+                let covered = if antialias {
+                    (radius + 0.5 - distance).clamp(0f32, 1f32)
+                } else if distance <= radius {
+                    1f32
+                } else {
+                    0f32
+                };
+
+                let index = (px + py * image_width) as usize;
+                if covered > coverage[index] {
+                    coverage[index] = covered;
+                    colours[index] = colour;
+                }
+            }
+        }
+    }
+
+    for (x, y, pixel) in img_buffer.enumerate_pixels_mut() {
+        let index = (x + y * image_width) as usize;
+        if coverage[index] > 0f32 {
+            *pixel = blend(pixel.data, colours[index], coverage[index]);
+        }
+    }
+}
+
+pub fn distance_to_segment(point: (f32, f32), from: (f32, f32), to: (f32, f32)) -> f32 {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = dx * dx + dy * dy;
+
+    // Find the closest spot along the segment, clamped to its ends
+    let t = if length == 0f32 {
+        0f32
+    } else {
+        (((point.0 - from.0) * dx + (point.1 - from.1) * dy) / length).clamp(0f32, 1f32)
+    };
+
+    let (nx, ny) = (from.0 + t * dx, from.1 + t * dy);
+    ((point.0 - nx).powi(2) + (point.1 - ny).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiny_maze() -> HashSet<(u32, u32)> {
+        // A 2 by 1 maze with the two cells joined
+        [(1, 1), (2, 1), (3, 1)].iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_format_from_filename() {
+        assert_eq!(
+            ImageFormat::from_filename("maze.png"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            ImageFormat::from_filename("out/Maze.JPEG"),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(
+            ImageFormat::from_filename("maze.ppm"),
+            Some(ImageFormat::Ppm)
+        );
+        assert_eq!(ImageFormat::from_filename("maze.tiff"), None);
+        assert_eq!(ImageFormat::from_filename("maze"), None);
+    }
+
+    #[test]
+    fn test_encode_every_format() {
+        let maze = tiny_maze();
+        let formats = [
+            ImageFormat::Png,
+            ImageFormat::Jpeg,
+            ImageFormat::Bmp,
+            ImageFormat::Ppm,
+            ImageFormat::Gif,
+        ];
+
+        for format in &formats {
+            let bytes = encode_image(solved_image(3, 5, &maze, &[]), *format).unwrap();
+            let decoded = image::load_from_memory_with_format(&bytes, format.encoding()).unwrap();
+            assert_eq!(image::GenericImage::dimensions(&decoded), (5, 3));
+        }
+    }
+
+    #[test]
+    fn test_single_pixel_render_matches_maze() {
+        let mut options = RenderOptions::new(Theme::Classic);
+        options.passage_width = 1;
+        options.wall_thickness = 1;
+        options.margin = 0;
+
+        let maze = tiny_maze();
+        let img = render_image(3, 5, &maze, None, &options);
+        assert_eq!(img.dimensions(), (5, 3));
+
+        for (x, y, pixel) in img.enumerate_pixels() {
+            let expected = if maze.contains(&(x, y)) { 255 } else { 0 };
+            assert_eq!(pixel.data, [expected; 3]);
+        }
+    }
+
+    #[test]
+    fn test_render_sizes() {
+        let mut options = RenderOptions::new(Theme::Classic);
+        options.passage_width = 6;
+        options.wall_thickness = 2;
+        options.margin = 3;
+
+        // Three walls and two passages across, two walls and a passage down
+        let img = render_image(3, 5, &tiny_maze(), None, &options);
+        assert_eq!(img.dimensions(), (3 + 3 * 2 + 2 * 6 + 3, 3 + 2 * 2 + 6 + 3));
+
+        // Margin, wall, then passage
+        assert_eq!(img.get_pixel(0, 0).data, [255; 3]);
+        assert_eq!(img.get_pixel(4, 4).data, [0; 3]);
+        assert_eq!(img.get_pixel(7, 7).data, [255; 3]);
+    }
+
+    #[test]
+    fn test_path_is_drawn() {
+        let mut options = RenderOptions::new(Theme::Dark);
+        options.antialias = false;
+
+        let maze = tiny_maze();
+        let path: Vec<&(u32, u32)> = vec![&(3, 1), &(2, 1), &(1, 1)];
+        let img = render_image(3, 5, &maze, Some(path), &options);
+
+        // The middle of the passage between the two cells
+        let (x, _) = options.band(2);
+        let (y, size) = options.band(1);
+        assert_eq!(
+            img.get_pixel(x, y + size / 2).data,
+            options.path_colour.unwrap()
+        );
+
+        // The edge of a passage is left alone
+        assert_eq!(img.get_pixel(x, y).data, options.passage_colour);
+    }
+
+    #[test]
+    fn test_walls_are_smoothed() {
+        let mut options = RenderOptions::new(Theme::Classic);
+        options.passage_width = 8;
+        options.wall_thickness = 4;
+        options.margin = 0;
+
+        // The outside corner of the top left post is rounded off, the middle
+        // of the wall next to it is solid
+        let img = render_image(3, 5, &tiny_maze(), None, &options);
+        let corner = img.get_pixel(0, 0).data;
+        assert!(corner[0] > 0 && corner[0] < 255);
+        assert_eq!(img.get_pixel(2, 0).data, [0; 3]);
+        assert_eq!(img.get_pixel(8, 2).data, [0; 3]);
+
+        // Without smoothing the walls are square blocks
+        options.antialias = false;
+        let img = render_image(3, 5, &tiny_maze(), None, &options);
+        assert_eq!(img.get_pixel(0, 0).data, [0; 3]);
+    }
+
+    #[test]
+    fn test_outside_is_background() {
+        let mut options = RenderOptions::new(Theme::Classic);
+        options.passage_width = 1;
+        options.wall_thickness = 1;
+        options.margin = 1;
+        options.background = Some([128, 128, 128]);
+
+        // The tiny maze with a third cell to its right left out of the maze
+        let img = render_image(3, 7, &tiny_maze(), None, &options);
+        assert_eq!(img.dimensions(), (9, 5));
+
+        // The margin, the walls around the maze, a passage, then outside
+        assert_eq!(img.get_pixel(0, 0).data, [128; 3]);
+        assert_eq!(img.get_pixel(1, 1).data, [0; 3]);
+        assert_eq!(img.get_pixel(5, 1).data, [0; 3]);
+        assert_eq!(img.get_pixel(2, 2).data, [255; 3]);
+        assert_eq!(img.get_pixel(6, 2).data, [128; 3]);
+        assert_eq!(img.get_pixel(7, 1).data, [128; 3]);
+    }
 }
@@ -1,52 +1,458 @@
 extern crate rand;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
 use std::time::Instant;
 
+mod analysis;
+mod animation;
+mod crc;
+mod difficulty;
+mod grids;
 mod imagecontrol;
 mod mazebuilder;
+mod mazefile;
 mod mazesolver;
+mod svg;
+#[cfg(test)]
+mod testing;
+mod text;
 
-pub fn create_and_save_maze(maze_height: u32, maze_width: u32) -> HashSet<(u32, u32)> {
+pub use analysis::{MazeStats, Validation};
+pub use animation::{AnimationFormat, AnimationOptions};
+pub use difficulty::{Level, Metric, Target};
+pub use grids::{
+    Direction, Edge, Grid, GridMaze, HexGrid, LevelGrid, PolarGrid, Stair, TriangleGrid, WeaveGrid,
+};
+pub use imagecontrol::{Gradient, ImageFormat, MazeImage, RenderOptions, Theme};
+pub use mazebuilder::Mask;
+pub use mazefile::{LoadError, LoadedMaze, MazeDocument, Metadata, Solution, Topology};
+pub use mazesolver::SolveReport;
+pub use svg::SvgOptions;
+pub use text::TextStyle;
+
+use imagecontrol::Heat;
+
+// Number of seeds tried when generating a maze to a target
+const TARGET_SEEDS: u64 = 1000;
+
+pub fn create_and_save_maze(maze_height: u32, maze_width: u32) -> io::Result<HashSet<(u32, u32)>> {
+    create_and_save_maze_as(maze_height, maze_width, "maze.png", ImageFormat::Png)
+}
+
+pub fn create_and_save_maze_as(
+    maze_height: u32,
+    maze_width: u32,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<HashSet<(u32, u32)>> {
     println!(
         "Generating Maze with height {} and width {}",
         maze_height, maze_width
     );
 
-    let height = if maze_height % 2 == 0 {
-        maze_height + 1
+    let height = make_odd(maze_height);
+    let width = make_odd(maze_width);
+
+    // Setup Timer
+    let timer = Instant::now();
+
+    let generator = select_maze_generator(height, width);
+    let maze = mazebuilder::generate_maze(generator, select_imperfect());
+    println!("Maze Generated in {:?}", timer.elapsed());
+
+    save_maze(height, width, &maze, filename, format)?;
+
+    Ok(maze)
+}
+
+/// Generates a maze, saving it as an image and also in the binary format
+/// along with which generator made it.
+pub fn create_and_archive_maze(
+    maze_height: u32,
+    maze_width: u32,
+    filename: &str,
+    image_filename: &str,
+    format: ImageFormat,
+) -> io::Result<HashSet<(u32, u32)>> {
+    let (loaded, metadata) = generate_with_metadata(maze_height, maze_width);
+
+    save_maze(
+        loaded.height,
+        loaded.width,
+        &loaded.passages,
+        image_filename,
+        format,
+    )?;
+
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, loaded.height, loaded.width
+    );
+    let timer = Instant::now();
+    mazefile::save_binary(&loaded, &metadata, filename)?;
+    println!("Maze saved in {:?}", timer.elapsed());
+
+    Ok(loaded.passages)
+}
+
+/// Generates mazes from a run of seeds until one falls within the target,
+/// saving it as an image and in the binary format along with its seed.
+///
+/// With `tune` set, dead ends are opened up into loops as needed to bring
+/// each maze within the target, otherwise only perfect mazes are made.
+#[allow(clippy::too_many_arguments)]
+pub fn create_maze_to_target(
+    maze_height: u32,
+    maze_width: u32,
+    target: &Target,
+    first_seed: u64,
+    tune: bool,
+    filename: &str,
+    image_filename: &str,
+    format: ImageFormat,
+) -> io::Result<Option<HashSet<(u32, u32)>>> {
+    println!(
+        "Generating Maze with height {} and width {} and {} between {} and {}",
+        maze_height,
+        maze_width,
+        target.metric.name(),
+        target.low,
+        target.high
+    );
+
+    let height = make_odd(maze_height);
+    let width = make_odd(maze_width);
+
+    let generator = select_maze_generator(height, width);
+    let seeds = first_seed..first_seed.saturating_add(TARGET_SEEDS);
+
+    // Setup Timer
+    let timer = Instant::now();
+
+    let found = if tune {
+        difficulty::tune_braid(generator, seeds, target)
     } else {
-        maze_height
+        difficulty::find_level(generator, 0.0, seeds, target)
     };
 
-    let width = if maze_width % 2 == 0 {
-        maze_width + 1
-    } else {
-        maze_width
+    let level = match found {
+        Some(level) => level,
+        None => {
+            println!(
+                "No maze within the target after {} seeds, taking {:?}",
+                TARGET_SEEDS,
+                timer.elapsed()
+            );
+            return Ok(None);
+        }
     };
 
-    // Setup Timer
+    println!(
+        "Found a maze with seed {} and braid {:.3} after {} attempts in {:?}",
+        level.seed,
+        level.braid,
+        level.attempts,
+        timer.elapsed()
+    );
+    println!(
+        "Solution length {}, {} decision points, dead end depth {:.1}, difficulty {:.1}",
+        level.stats.solution_length,
+        level.stats.decision_points,
+        level.stats.average_dead_end_depth,
+        level.stats.difficulty
+    );
+
+    save_maze(height, width, &level.maze, image_filename, format)?;
+
+    let mut name = String::from(generator.name());
+    if level.braid > 0.0 {
+        name.push_str(&format!("+braid={}", level.braid));
+    }
+
+    let loaded = LoadedMaze {
+        height,
+        width,
+        passages: level.maze,
+        start: (1, 1),
+        end: (width - 2, height - 2),
+    };
+    let metadata = Metadata {
+        topology: Topology::Grid,
+        generator: name,
+        seed: Some(level.seed),
+    };
+
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    mazefile::save_binary(&loaded, &metadata, filename)?;
+
+    Ok(Some(loaded.passages))
+}
+
+/// Generates a maze and solves it with every solver, saving the maze and all
+/// of the solutions as JSON.
+pub fn create_and_export_maze(
+    maze_height: u32,
+    maze_width: u32,
+    filename: &str,
+) -> io::Result<HashSet<(u32, u32)>> {
+    let (loaded, metadata) = generate_with_metadata(maze_height, maze_width);
+    let mut document = mazefile::MazeDocument::new(&loaded, &metadata)?;
+
+    println!("Solving Maze with every solver...");
+    for solver in &mazesolver::Solver::ALL {
+        let (path, report) =
+            mazesolver::solve_maze(*solver, loaded.start, loaded.end, &loaded.passages, false);
+
+        match path {
+            Some(path) => document.add_solution(solver.name(), &path, report),
+            None => println!("{} found no path!", solver.name()),
+        }
+    }
+
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, loaded.height, loaded.width
+    );
     let timer = Instant::now();
+    mazefile::save_json(&document, filename)?;
+    println!("Maze saved in {:?}", timer.elapsed());
+
+    Ok(loaded.passages)
+}
+
+pub fn create_and_animate_maze(
+    maze_height: u32,
+    maze_width: u32,
+    options: &AnimationOptions,
+    image_filename: &str,
+    format: ImageFormat,
+) -> io::Result<HashSet<(u32, u32)>> {
+    println!(
+        "Generating Maze with height {} and width {}",
+        maze_height, maze_width
+    );
+
+    let height = make_odd(maze_height);
+    let width = make_odd(maze_width);
 
     let generator = select_maze_generator(height, width);
-    let maze = mazebuilder::generate_maze(generator, select_imperfect());
+    let imperfect = select_imperfect();
+
+    // Setup Timer
+    let timer = Instant::now();
+
+    let (maze, carving) = mazebuilder::generate_maze_recorded(generator, imperfect);
     println!("Maze Generated in {:?}", timer.elapsed());
 
-    save_maze(height, width, &maze);
+    save_maze(height, width, &maze, image_filename, format)?;
 
-    maze
+    let filename = format!("maze-generation.{}", options.format.extension());
+    println!(
+        "Saving {} with {} carving steps",
+        filename,
+        carving.steps.len()
+    );
+    let timer = Instant::now();
+    animation::animate_generation(
+        height,
+        width,
+        &carving.initial,
+        &carving.steps,
+        options,
+        &filename,
+    )?;
+    println!("Animation saved in {:?}", timer.elapsed());
+
+    Ok(maze)
 }
 
-pub fn solve_maze(height: u32, width: u32, maze: &HashSet<(u32, u32)>) {
-    println!("Solving Maze...");
+pub fn solve_maze(height: u32, width: u32, maze: &HashSet<(u32, u32)>) -> io::Result<()> {
+    solve_maze_as(height, width, maze, "solved-maze.png", ImageFormat::Png)
+}
 
-    let height = if height % 2 == 0 { height + 1 } else { height };
+pub fn solve_maze_as(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!("Solving Maze...");
 
-    let width = if width % 2 == 0 { width + 1 } else { width };
+    let height = make_odd(height);
+    let width = make_odd(width);
 
     let start_point = (1, 1);
     let end_point = (width - 2, height - 2);
 
+    solve_and_save(
+        height,
+        width,
+        maze,
+        start_point,
+        end_point,
+        filename,
+        format,
+    )
+}
+
+/// Solves a maze that was loaded from a file, saving the solution as an image.
+pub fn solve_loaded_maze(
+    loaded: &LoadedMaze,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!("Solving Maze...");
+
+    solve_and_save(
+        loaded.height,
+        loaded.width,
+        &loaded.passages,
+        loaded.start,
+        loaded.end,
+        filename,
+        format,
+    )
+}
+
+/// Loads the shape for a masked maze, from a text file if it ends in .txt
+/// and otherwise from an image where dark pixels are inside the maze.
+pub fn load_mask(filename: &str, threshold: u8) -> Result<Mask, LoadError> {
+    println!("Loading mask from {}", filename);
+
+    let mask = if filename.ends_with(".txt") {
+        mazefile::load_mask_text(filename)?
+    } else {
+        mazefile::load_mask_image(filename, threshold)?
+    };
+
+    println!(
+        "Loaded mask with {} of its {} by {} cells inside",
+        mask.count(),
+        mask.columns(),
+        mask.rows()
+    );
+
+    Ok(mask)
+}
+
+pub fn load_maze_image(filename: &str, threshold: u8) -> Result<LoadedMaze, LoadError> {
+    println!("Loading maze from {}", filename);
+
+    let timer = Instant::now();
+    let loaded = mazefile::load_png(filename, threshold)?;
+    println!(
+        "Loaded maze with height {} and width {} in {:?}",
+        loaded.height,
+        loaded.width,
+        timer.elapsed()
+    );
+
+    Ok(loaded)
+}
+
+pub fn load_maze_text(filename: &str) -> Result<LoadedMaze, LoadError> {
+    println!("Loading maze from {}", filename);
+
+    let timer = Instant::now();
+    let loaded = mazefile::load_ascii(filename)?;
+    println!(
+        "Loaded maze with height {} and width {} in {:?}",
+        loaded.height,
+        loaded.width,
+        timer.elapsed()
+    );
+
+    Ok(loaded)
+}
+
+pub fn load_maze_binary(filename: &str) -> Result<(LoadedMaze, Metadata), LoadError> {
+    println!("Loading maze from {}", filename);
+
+    let timer = Instant::now();
+    let (loaded, metadata) = mazefile::load_binary(filename)?;
+    println!(
+        "Loaded maze with height {} and width {} in {:?}",
+        loaded.height,
+        loaded.width,
+        timer.elapsed()
+    );
+
+    match metadata.seed {
+        Some(seed) => println!("Made by {} with seed {}", metadata.generator, seed),
+        None => println!("Made by {}", metadata.generator),
+    }
+
+    Ok((loaded, metadata))
+}
+
+/// Loads a maze saved as JSON, listing the solutions that were saved with it.
+pub fn load_maze_json(filename: &str) -> Result<(LoadedMaze, Metadata), LoadError> {
+    println!("Loading maze from {}", filename);
+
+    let timer = Instant::now();
+    let document = mazefile::load_json(filename)?;
+    let (loaded, metadata) = document.to_maze()?;
+    println!(
+        "Loaded maze with height {} and width {} in {:?}",
+        loaded.height,
+        loaded.width,
+        timer.elapsed()
+    );
+
+    for solution in &document.solutions {
+        println!(
+            "Saved solution from {} with path length {}",
+            solution.solver, solution.report.path_length
+        );
+    }
+
+    Ok((loaded, metadata))
+}
+
+/// Saves the maze as text, with `#` for walls and `S` and `E` marking the
+/// start and end.
+pub fn save_maze_text(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    filename: &str,
+) -> io::Result<()> {
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    let loaded = LoadedMaze {
+        height,
+        width,
+        passages: maze.clone(),
+        start: (1, 1),
+        end: (width - 2, height - 2),
+    };
+
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    mazefile::save_ascii(&loaded, filename)
+}
+
+fn solve_and_save(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    start_point: (u32, u32),
+    end_point: (u32, u32),
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
     println!(
         "Finding path from point {:?} to {:?}",
         start_point, end_point
@@ -54,29 +460,957 @@ pub fn solve_maze(height: u32, width: u32, maze: &HashSet<(u32, u32)>) {
 
     let solver = select_maze_solver();
     let timer = Instant::now();
-    let path = mazesolver::solve_maze(solver, start_point, end_point, maze);
 
-    if path == None {
-        println!("Something went wrong and no path was found!");
+    // Some solvers hand back extra detail which is drawn under the path
+    match solver {
+        mazesolver::Solver::Tremaux => {
+            let (path, marks, report) =
+                mazesolver::solve_tremaux(start_point, end_point, maze, false);
+            if let Some(path) = report_path(path, &report, timer) {
+                save_marked_maze(height, width, maze, path, &marks, filename, format)?;
+            }
+        }
+        mazesolver::Solver::DeadEndFilling => {
+            let (path, filled, report) =
+                mazesolver::solve_dead_end_filling(start_point, end_point, maze, false);
+            if let Some(path) = report_path(path, &report, timer) {
+                save_filled_maze(height, width, maze, path, &filled, filename, format)?;
+            }
+        }
+        _ => {
+            let (path, report) =
+                mazesolver::solve_maze(solver, start_point, end_point, maze, false);
+            if let Some(path) = report_path(path, &report, timer) {
+                save_solved_maze(height, width, maze, path, filename, format)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn animate_solve_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    options: &AnimationOptions,
+) -> io::Result<()> {
+    println!("Solving Maze...");
+
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    let start_point = (1, 1);
+    let end_point = (width - 2, height - 2);
+
+    let solver = select_maze_solver();
+    let timer = Instant::now();
+
+    // Keep every expansion so that it can be played back
+    let (path, report) = mazesolver::solve_maze(solver, start_point, end_point, maze, true);
+    let path = report_path(path, &report, timer).unwrap_or_default();
+    let trace = report.trace.unwrap_or_default();
+
+    let filename = format!("maze-solve.{}", options.format.extension());
+    println!("Saving {} with {} solver steps", filename, trace.len());
+    let timer = Instant::now();
+    animation::animate_solve(height, width, maze, &trace, &path, options, &filename)?;
+    println!("Animation saved in {:?}", timer.elapsed());
+
+    Ok(())
+}
+
+pub fn find_solutions(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    limit: usize,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!("Looking for solutions...");
+
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    let start_point = (1, 1);
+    let end_point = (width - 2, height - 2);
+
+    let timer = Instant::now();
+    let shortest = analysis::count_shortest_paths(start_point, end_point, maze);
+    println!("There are {} shortest paths through the maze", shortest);
+
+    let solutions = analysis::find_solutions(start_point, end_point, maze, limit);
+    println!(
+        "Found {} solutions in {:?} (looking for up to {})",
+        solutions.len(),
+        timer.elapsed(),
+        limit
+    );
+
+    if !solutions.is_empty() {
+        save_solutions_maze(height, width, maze, &solutions, filename, format)?;
+    }
+
+    Ok(())
+}
+
+/// Checks the maze over, printing whether it is closed in, joined up and
+/// perfect, and where any loops are.
+pub fn check_maze(height: u32, width: u32, maze: &HashSet<(u32, u32)>) -> Validation {
+    check(make_odd(height), make_odd(width), (1, 1), maze)
+}
+
+pub fn check_loaded_maze(loaded: &LoadedMaze) -> Validation {
+    check(loaded.height, loaded.width, loaded.start, &loaded.passages)
+}
+
+fn check(height: u32, width: u32, start: (u32, u32), maze: &HashSet<(u32, u32)>) -> Validation {
+    println!("Checking Maze...");
+
+    let timer = Instant::now();
+    let validation = analysis::validate(start, height, width, maze);
+    println!("Maze checked in {:?}", timer.elapsed());
+
+    println!(
+        "{} passages joined by {} links",
+        validation.passages, validation.links
+    );
+
+    if !validation.border_closed {
+        println!("The border has a gap in it");
+    }
+
+    if !validation.missing_cells.is_empty() {
+        println!(
+            "{} cells are walled up, the first at {:?}",
+            validation.missing_cells.len(),
+            validation.missing_cells[0]
+        );
+    }
+
+    for region in &validation.isolated_regions {
+        println!(
+            "{} passages around {:?} can't be reached from the start",
+            region.len(),
+            region[0]
+        );
+    }
+
+    for cycle in &validation.cycles {
+        println!("Loop of {} passages through {:?}", cycle.len(), cycle[0]);
+    }
+
+    if validation.is_perfect() {
+        println!("The maze is perfect");
+    } else if validation.is_valid() {
+        println!("The maze is valid, with {} loops", validation.cycles.len());
     } else {
-        println!("We have a path!");
-        println!("Maze solved in {:?}", timer.elapsed());
-        save_solved_maze(height, width, maze, path.unwrap());
+        println!("The maze is not valid");
     }
+
+    validation
+}
+
+/// Measures the maze and prints how hard it is to solve.
+pub fn maze_stats(height: u32, width: u32, maze: &HashSet<(u32, u32)>) -> MazeStats {
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    measure((1, 1), (width - 2, height - 2), maze)
+}
+
+pub fn loaded_maze_stats(loaded: &LoadedMaze) -> MazeStats {
+    measure(loaded.start, loaded.end, &loaded.passages)
 }
 
-fn save_maze(height: u32, width: u32, maze: &HashSet<(u32, u32)>) {
-    println!("Saving image with height {} and width {}", height, width);
+fn measure(start: (u32, u32), end: (u32, u32), maze: &HashSet<(u32, u32)>) -> MazeStats {
+    println!("Measuring Maze...");
+
     let timer = Instant::now();
-    imagecontrol::generate_image(height as u32, width as u32, maze);
+    let stats = analysis::stats(start, end, maze);
+    println!("Maze measured in {:?}", timer.elapsed());
+
+    println!(
+        "Passages:          {} ({} cells)",
+        stats.passages, stats.cells
+    );
+    println!(
+        "Dead ends:         {} ({:.1}% of cells)",
+        stats.dead_ends,
+        stats.dead_end_ratio * 100.0
+    );
+    println!("Junctions:         {}", stats.junctions);
+    println!(
+        "Corridors:         {} (average length {:.1})",
+        stats.corridors, stats.average_corridor_length
+    );
+    println!("River:             {:.2}", stats.river);
+    println!(
+        "Dead end depth:    {:.1} on average, {} at most",
+        stats.average_dead_end_depth, stats.max_dead_end_depth
+    );
+    println!(
+        "Solution length:   {} (through {:.1}% of cells)",
+        stats.solution_length,
+        stats.solution_ratio * 100.0
+    );
+    println!("Decision points:   {}", stats.decision_points);
+    println!("Tortuosity:        {:.2}", stats.tortuosity);
+    println!("Difficulty:        {:.1}", stats.difficulty);
+
+    stats
+}
+
+/// Generates a maze of the same size with each generator, with and without
+/// loops, and prints their statistics side by side.
+pub fn compare_generators(height: u32, width: u32) {
+    println!("Comparing Generators...");
+
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    let start_point = (1, 1);
+    let end_point = (width - 2, height - 2);
+
+    let generators = vec![
+        (mazebuilder::Generator::DFS { height, width }, false),
+        (mazebuilder::Generator::Kruskal { height, width }, false),
+        (mazebuilder::Generator::DFS { height, width }, true),
+        (mazebuilder::Generator::Kruskal { height, width }, true),
+    ];
+
+    let mut rows = Vec::new();
+
+    for (generator, imperfect) in generators {
+        let mut name = String::from(generator.name());
+        if imperfect {
+            name.push_str("+imperfect");
+        }
+
+        let maze = mazebuilder::generate_maze(generator, imperfect);
+        rows.push((name, analysis::stats(start_point, end_point, &maze)));
+    }
+
+    println!(
+        "{:<18} {:>9} {:>9} {:>9} {:>7} {:>9} {:>9} {:>9} {:>10} {:>10}",
+        "Generator",
+        "Dead ends",
+        "Junctions",
+        "Corridor",
+        "River",
+        "Depth",
+        "Solution",
+        "Decisions",
+        "Tortuosity",
+        "Difficulty"
+    );
+
+    for (name, stats) in rows {
+        println!(
+            "{:<18} {:>8.1}% {:>9} {:>9.1} {:>7.2} {:>9.1} {:>9} {:>9} {:>10.2} {:>10.1}",
+            name,
+            stats.dead_end_ratio * 100.0,
+            stats.junctions,
+            stats.average_corridor_length,
+            stats.river,
+            stats.average_dead_end_depth,
+            stats.solution_length,
+            stats.decision_points,
+            stats.tortuosity,
+            stats.difficulty
+        );
+    }
+}
+
+/// Runs every solver over the maze, printing how much work each one did and
+/// saving a heatmap for each with the solver's name added to the filename.
+pub fn compare_solvers(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!("Comparing Solvers...");
+
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    let start_point = (1, 1);
+    let end_point = (width - 2, height - 2);
+
+    println!(
+        "{:<20} {:>10} {:>10} {:>10} {:>10} {:>14}",
+        "Solver", "Expanded", "Frontier", "Revisits", "Path", "Time"
+    );
+
+    let mut heatmaps = Vec::new();
+
+    for &solver in mazesolver::Solver::ALL.iter() {
+        let timer = Instant::now();
+        let (path, report) = mazesolver::solve_maze(solver, start_point, end_point, maze, true);
+        let elapsed = format!("{:?}", timer.elapsed());
+
+        println!(
+            "{:<20} {:>10} {:>10} {:>10} {:>10} {:>14}",
+            solver.name(),
+            report.expanded,
+            report.max_frontier,
+            report.revisits,
+            report.path_length,
+            elapsed
+        );
+
+        // Searches are coloured by the order they looked at each node, while
+        // solvers that walk the maze are coloured by how often they stepped on it.
+        let heat = if solver.walks() {
+            Heat::Visits
+        } else {
+            Heat::Order
+        };
+
+        let filename = name_with_suffix(filename, solver.name());
+        let trace = report.trace.unwrap_or_default();
+        heatmaps.push((filename, path.unwrap_or_default(), trace, heat));
+    }
+
+    // Draw how much of the maze each solver had to look at
+    for (filename, path, trace, heat) in heatmaps {
+        save_heatmap_maze(height, width, maze, path, &trace, heat, &filename, format)?;
+    }
+
+    Ok(())
+}
+
+/// Draws the maze in memory, one pixel per passage, without saving it.
+pub fn maze_to_image(height: u32, width: u32, maze: &HashSet<(u32, u32)>) -> MazeImage {
+    imagecontrol::solved_image(make_odd(height), make_odd(width), maze, &[])
+}
+
+/// Draws the maze and encodes it as an image file in memory, without saving it.
+pub fn maze_to_bytes(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    format: ImageFormat,
+) -> io::Result<Vec<u8>> {
+    imagecontrol::encode_image(maze_to_image(height, width, maze), format)
+}
+
+/// Draws the maze as text, with the path marked on it if there is one.
+pub fn maze_to_text(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    path: Option<&[&(u32, u32)]>,
+    style: &TextStyle,
+) -> String {
+    text::render_text(make_odd(height), make_odd(width), maze, path, style)
+}
+
+pub fn print_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    solve: bool,
+    style: &TextStyle,
+) {
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    let start_point = (1, 1);
+    let end_point = (width - 2, height - 2);
+
+    let path = if solve {
+        solve_for_drawing(start_point, end_point, maze)
+    } else {
+        None
+    };
+
+    print!(
+        "{}",
+        maze_to_text(
+            height,
+            width,
+            maze,
+            path.as_ref().map(|path| &path[..]),
+            style
+        )
+    );
+}
+
+pub fn save_distance_map(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    gradient: &Gradient,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!("Measuring distances from the start...");
+
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    let timer = Instant::now();
+    let distances = analysis::distances_from((1, 1), maze);
+    println!(
+        "Furthest passage is {} steps away, measured in {:?}",
+        distances.values().max().unwrap_or(&0),
+        timer.elapsed()
+    );
+
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    imagecontrol::generate_distance_image(
+        height, width, maze, &distances, gradient, filename, format,
+    )?;
+    println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(())
+}
+
+pub fn save_svg_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    solve: bool,
+    options: &SvgOptions,
+    filename: &str,
+) -> io::Result<()> {
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    let start_point = (1, 1);
+    let end_point = (width - 2, height - 2);
+
+    // Draw the solution over the walls if one was asked for
+    let path = if solve {
+        solve_for_drawing(start_point, end_point, maze)
+    } else {
+        None
+    };
+
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    svg::generate_svg(
+        height,
+        width,
+        maze,
+        path.as_ref().map(|path| &path[..]),
+        options,
+        filename,
+    )?;
+    println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(())
+}
+
+pub fn render_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    solve: bool,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    let height = make_odd(height);
+    let width = make_odd(width);
+
+    let start_point = (1, 1);
+    let end_point = (width - 2, height - 2);
+
+    let path = if solve {
+        solve_for_drawing(start_point, end_point, maze)
+    } else {
+        None
+    };
+
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    imagecontrol::generate_rendered_image(height, width, maze, path, options, filename, format)?;
     println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(())
 }
 
-fn save_solved_maze(height: u32, width: u32, maze: &HashSet<(u32, u32)>, path: Vec<&(u32, u32)>) {
-    println!("Saving image with height {} and width {}", height, width);
+/// Generates a maze in the shape of the mask, rendering it with everything
+/// outside the shape left in the background colour. The maze runs from the
+/// first cell inside the mask to the last, reading row by row.
+pub fn render_masked_maze(
+    mask: &Mask,
+    solve: bool,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<LoadedMaze> {
+    let (height, width) = mask.size();
+    println!(
+        "Generating Masked Maze with height {} and width {}",
+        height, width
+    );
+
+    let generator = select_maze_generator(height, width);
+    let imperfect = select_imperfect();
+
+    // Setup Timer
     let timer = Instant::now();
-    imagecontrol::generate_solved_image(height as u32, width as u32, maze, path);
+
+    let maze = mazebuilder::generate_masked_maze(generator, mask, imperfect);
+    println!("Maze Generated in {:?}", timer.elapsed());
+
+    let (start_point, end_point) = masked_ends(mask, &maze);
+
+    let path = if solve {
+        solve_for_drawing(start_point, end_point, &maze)
+    } else {
+        None
+    };
+
+    // Shade the outside halfway between the walls and passages unless told otherwise
+    let mut options = options.clone();
+    if options.background.is_none() {
+        let mut background = options.passage_colour;
+        for (value, wall) in background.iter_mut().zip(options.wall_colour.iter()) {
+            *value = ((u16::from(*value) + u16::from(*wall)) / 2) as u8;
+        }
+        options.background = Some(background);
+    }
+
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    imagecontrol::generate_rendered_image(height, width, &maze, path, &options, filename, format)?;
     println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(LoadedMaze {
+        height,
+        width,
+        passages: maze,
+        start: start_point,
+        end: end_point,
+    })
+}
+
+// The first and last cells, top left to bottom right, of the biggest part of
+// a masked maze, so the end can always be reached from the start
+fn masked_ends(mask: &Mask, maze: &HashSet<(u32, u32)>) -> ((u32, u32), (u32, u32)) {
+    let cells = mask.nodes();
+    let mut reached = HashSet::new();
+    let mut biggest = 0;
+    let mut ends = ((1, 1), (1, 1));
+
+    for &cell in &cells {
+        if reached.contains(&cell) {
+            continue;
+        }
+
+        let part = analysis::distances_from(cell, maze);
+        let size = cells.iter().filter(|node| part.contains_key(node)).count();
+        let last = *cells
+            .iter()
+            .rev()
+            .find(|node| part.contains_key(node))
+            .unwrap_or(&cell);
+        reached.extend(part.keys().cloned());
+
+        if size > biggest {
+            biggest = size;
+            ends = (cell, last);
+        }
+    }
+
+    ends
+}
+
+/// Generates a maze on a grid of hexagons, saving it as an image and as an
+/// SVG beside it. The maze runs from the top left hexagon to the bottom right.
+pub fn create_hex_maze(
+    columns: u32,
+    rows: u32,
+    solve: bool,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<GridMaze<HexGrid>> {
+    println!(
+        "Generating Hex Maze with {} columns and {} rows",
+        columns, rows
+    );
+
+    create_grid_maze(
+        HexGrid::new(columns, rows),
+        solve,
+        options,
+        filename,
+        format,
+    )
+}
+
+/// Generates a circular maze of rings around a middle cell, saving it as an
+/// image and as an SVG beside it. The maze runs from the middle out to the
+/// edge.
+pub fn create_polar_maze(
+    rings: u32,
+    solve: bool,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<GridMaze<PolarGrid>> {
+    println!("Generating Circular Maze with {} rings", rings);
+
+    create_grid_maze(PolarGrid::new(rings), solve, options, filename, format)
+}
+
+/// Generates a maze on a grid of triangles pointing up and down, saving it
+/// as an image and as an SVG beside it. The maze runs from the top left
+/// triangle to the bottom right.
+pub fn create_triangle_maze(
+    columns: u32,
+    rows: u32,
+    solve: bool,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<GridMaze<TriangleGrid>> {
+    println!(
+        "Generating Triangle Maze with {} columns and {} rows",
+        columns, rows
+    );
+
+    create_grid_maze(
+        TriangleGrid::new(columns, rows),
+        solve,
+        options,
+        filename,
+        format,
+    )
+}
+
+/// Generates a maze spread over several levels joined by stairs, saving it as
+/// an image and as an SVG beside it with each level drawn side by side. The
+/// maze runs from the top left of the bottom level to the bottom right of the
+/// top.
+pub fn create_level_maze(
+    columns: u32,
+    rows: u32,
+    levels: u32,
+    solve: bool,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<GridMaze<LevelGrid>> {
+    println!(
+        "Generating Maze with {} columns, {} rows and {} levels",
+        columns, rows, levels
+    );
+
+    let grid = LevelGrid::new(columns, rows, levels);
+    create_grid_maze(grid, solve, options, filename, format)
+}
+
+/// Generates a weave maze, where passages can tunnel under each other,
+/// saving it as an image and as an SVG beside it. The maze runs from the top
+/// left to the bottom right.
+pub fn create_weave_maze(
+    columns: u32,
+    rows: u32,
+    solve: bool,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<GridMaze<WeaveGrid>> {
+    println!(
+        "Generating Weave Maze with {} columns and {} rows",
+        columns, rows
+    );
+
+    create_grid_maze(
+        WeaveGrid::new(columns, rows),
+        solve,
+        options,
+        filename,
+        format,
+    )
+}
+
+fn create_grid_maze<G: Grid>(
+    grid: G,
+    solve: bool,
+    options: &RenderOptions,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<GridMaze<G>> {
+    let algorithm = select_grid_algorithm();
+    let braid = if select_imperfect() {
+        mazebuilder::IMPERFECT_BRAID
+    } else {
+        0.0
+    };
+
+    // Setup Timer
+    let timer = Instant::now();
+
+    let maze = mazebuilder::generate_grid(grid, algorithm, braid, &mut rand::thread_rng());
+    println!(
+        "Maze Generated by {} in {:?}",
+        algorithm.name(),
+        timer.elapsed()
+    );
+
+    let path = if solve {
+        println!("Solving Maze...");
+
+        let solver = select_maze_solver();
+        let timer = Instant::now();
+        let (start, end) = (maze.grid.start(), maze.grid.end());
+        let (path, report) = mazesolver::solve_grid(solver, &maze, start, end, false);
+
+        report_path(path, &report, timer)
+    } else {
+        None
+    };
+    let path = path.as_ref().map(|path| &path[..]);
+
+    let timer = Instant::now();
+    println!("Saving {}", filename);
+    grids::generate_grid_image(&maze, path, options, filename, format)?;
+
+    let filename = Path::new(filename).with_extension("svg");
+    let filename = filename.to_string_lossy();
+    println!("Saving {}", filename);
+    grids::generate_grid_svg(&maze, path, &SvgOptions::new(), &filename)?;
+    println!("Images saved in {:?}", timer.elapsed());
+
+    Ok(maze)
+}
+
+// Adds a suffix to the end of the file name, before its extension, so that
+// "heatmap.png" becomes "heatmap-bfs.png"
+fn name_with_suffix(filename: &str, suffix: &str) -> String {
+    let path = Path::new(filename);
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(filename);
+    let name = match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => format!("{}-{}.{}", stem, suffix, extension),
+        None => format!("{}-{}", stem, suffix),
+    };
+
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+// Mazes need an odd size so that they have a wall all the way around
+fn make_odd(value: u32) -> u32 {
+    if value.is_multiple_of(2) {
+        value + 1
+    } else {
+        value
+    }
+}
+
+// Asks which generator to use and makes a maze with it, remembering how it
+// was made so that it can be saved alongside the maze
+fn generate_with_metadata(maze_height: u32, maze_width: u32) -> (LoadedMaze, Metadata) {
+    println!(
+        "Generating Maze with height {} and width {}",
+        maze_height, maze_width
+    );
+
+    let height = make_odd(maze_height);
+    let width = make_odd(maze_width);
+
+    let generator = select_maze_generator(height, width);
+    let imperfect = select_imperfect();
+
+    let mut name = String::from(generator.name());
+    if imperfect {
+        name.push_str("+imperfect");
+    }
+
+    // Setup Timer
+    let timer = Instant::now();
+
+    let maze = mazebuilder::generate_maze(generator, imperfect);
+    println!("Maze Generated in {:?}", timer.elapsed());
+
+    let loaded = LoadedMaze {
+        height,
+        width,
+        passages: maze,
+        start: (1, 1),
+        end: (width - 2, height - 2),
+    };
+    let metadata = Metadata {
+        topology: Topology::Grid,
+        generator: name,
+        seed: None,
+    };
+
+    (loaded, metadata)
+}
+
+fn solve_for_drawing(
+    start_point: (u32, u32),
+    end_point: (u32, u32),
+    maze: &HashSet<(u32, u32)>,
+) -> Option<Vec<&(u32, u32)>> {
+    println!("Solving Maze...");
+
+    let solver = select_maze_solver();
+    let timer = Instant::now();
+    let (path, report) = mazesolver::solve_maze(solver, start_point, end_point, maze, false);
+
+    report_path(path, &report, timer)
+}
+
+fn report_path<T, N>(path: Option<T>, report: &SolveReport<N>, timer: Instant) -> Option<T> {
+    match path {
+        None => println!("Something went wrong and no path was found!"),
+        Some(_) => {
+            println!("We have a path!");
+            println!("Maze solved in {:?}", timer.elapsed());
+        }
+    }
+
+    println!(
+        "Expanded {} nodes ({} revisits), largest frontier {}, path length {}",
+        report.expanded, report.revisits, report.max_frontier, report.path_length
+    );
+
+    path
+}
+
+fn save_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    imagecontrol::generate_image(height, width, maze, filename, format)?;
+    println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(())
+}
+
+fn save_solved_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    path: Vec<&(u32, u32)>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    imagecontrol::generate_solved_image(height, width, maze, path, filename, format)?;
+    println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(())
+}
+
+fn save_marked_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    path: Vec<&(u32, u32)>,
+    marks: &HashMap<&(u32, u32), u8>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    imagecontrol::generate_marked_image(height, width, maze, path, marks, filename, format)?;
+    println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(())
+}
+
+fn save_filled_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    path: Vec<&(u32, u32)>,
+    filled: &HashSet<&(u32, u32)>,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    imagecontrol::generate_filled_image(height, width, maze, path, filled, filename, format)?;
+    println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(())
+}
+
+fn save_solutions_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    solutions: &[Vec<&(u32, u32)>],
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    imagecontrol::generate_solutions_image(height, width, maze, solutions, filename, format)?;
+    println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_heatmap_maze(
+    height: u32,
+    width: u32,
+    maze: &HashSet<(u32, u32)>,
+    path: Vec<&(u32, u32)>,
+    trace: &[(u32, u32)],
+    heat: Heat,
+    filename: &str,
+    format: ImageFormat,
+) -> io::Result<()> {
+    println!(
+        "Saving {} with height {} and width {}",
+        filename, height, width
+    );
+    let timer = Instant::now();
+    imagecontrol::generate_heatmap_image(height, width, maze, path, trace, heat, filename, format)?;
+    println!("Image saved in {:?}", timer.elapsed());
+
+    Ok(())
 }
 
 fn select_maze_generator(height: u32, width: u32) -> mazebuilder::Generator {
@@ -108,6 +1442,11 @@ fn select_maze_generator(height: u32, width: u32) -> mazebuilder::Generator {
     }
 }
 
+// Grids carry their own size, so only the algorithm is needed
+fn select_grid_algorithm() -> mazebuilder::Algorithm {
+    select_maze_generator(0, 0).algorithm()
+}
+
 fn select_maze_solver() -> mazesolver::Solver {
     use std::io;
     let mut input = String::new();
@@ -116,6 +1455,9 @@ fn select_maze_solver() -> mazesolver::Solver {
     println!("1. Breadth First Search,");
     println!("2. Left-turn,");
     println!("3. A*,");
+    println!("4. Pledge,");
+    println!("5. Tremaux,");
+    println!("6. Dead-end filling,");
     io::stdin()
         .read_line(&mut input)
         .expect("select_maze_solver -- unable to parse console input!");
@@ -132,6 +1474,9 @@ fn select_maze_solver() -> mazesolver::Solver {
         1 => mazesolver::Solver::BFS,
         2 => mazesolver::Solver::LeftTurn,
         3 => mazesolver::Solver::AStar,
+        4 => mazesolver::Solver::Pledge,
+        5 => mazesolver::Solver::Tremaux,
+        6 => mazesolver::Solver::DeadEndFilling,
         _ => {
             println!("unrecognised option {}, defaulting to BFS", option);
             mazesolver::Solver::BFS
@@ -1,36 +1,325 @@
 extern crate mazegenerator;
+use std::env;
 use std::io;
+use std::path::Path;
 
 fn main() {
+    // `mazegenerator stats` compares the generators, or measures the maze in
+    // a file when it is given one
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("stats") {
+        match args.get(1) {
+            Some(filename) => match load_maze(filename) {
+                Ok(maze) => {
+                    mazegenerator::loaded_maze_stats(&maze);
+                }
+                Err(err) => println!("Unable to load {}: {}", filename, err),
+            },
+            None => {
+                let (height, width) = get_maze_size();
+                mazegenerator::compare_generators(height, width);
+            }
+        }
+        return;
+    }
+
     // Check if the user wants to generate and solve a maze, or
     // just generate one.
     let mode = get_mode_option();
 
+    // Loaded mazes already have a size
+    if mode == 10 {
+        let filename = get_input_file();
+
+        match load_maze(&filename) {
+            Ok(maze) => {
+                mazegenerator::check_loaded_maze(&maze);
+                let (output, format) = get_output_file("solved-maze.png");
+                report_save(mazegenerator::solve_loaded_maze(&maze, &output, format));
+            }
+            Err(err) => println!("Unable to load {}: {}", filename, err),
+        }
+        return;
+    }
+
+    // Masked mazes take their size from the mask
+    if mode == 17 {
+        let filename = get_mask_file();
+        let threshold = if filename.ends_with(".txt") {
+            0
+        } else {
+            get_mask_threshold()
+        };
+
+        match mazegenerator::load_mask(&filename, threshold) {
+            Ok(mask) => {
+                let (output, format) = get_output_file("masked-maze.png");
+                let options = get_render_options();
+                let solve = get_drawn_solution();
+                let rendered =
+                    mazegenerator::render_masked_maze(&mask, solve, &options, &output, format);
+                report_save(rendered.map(|_| ()));
+            }
+            Err(err) => println!("Unable to load {}: {}", filename, err),
+        }
+        return;
+    }
+
+    // Circular mazes are measured in rings
+    if mode == 19 {
+        let rings = get_rings();
+        let (filename, format) = get_output_file("circle-maze.png");
+        let options = get_render_options();
+        let solve = get_drawn_solution();
+        let created = mazegenerator::create_polar_maze(rings, solve, &options, &filename, format);
+        report_save(created.map(|_| ()));
+        return;
+    }
+
     // Get the size of the maze to be generated
     let (height, width) = get_maze_size();
 
     match mode {
         1 => {
-            let maze = mazegenerator::create_and_save_maze(height, width);
+            let (filename, format) = get_output_file("maze.png");
+            let maze =
+                match mazegenerator::create_and_save_maze_as(height, width, &filename, format) {
+                    Ok(maze) => maze,
+                    Err(err) => return report_save(Err(err)),
+                };
+            let solved = prefixed_filename(&filename, "solved");
             let mut solve = true;
             while solve {
-                mazegenerator::solve_maze(height, width, &maze);
+                report_save(mazegenerator::solve_maze_as(
+                    height, width, &maze, &solved, format,
+                ));
                 solve = get_solver_retry();
             }
         }
         2 => {
-            let _maze = mazegenerator::create_and_save_maze(height, width);
+            let (filename, format) = get_output_file("maze.png");
+            let created = mazegenerator::create_and_save_maze_as(height, width, &filename, format);
+            report_save(created.map(|_| ()));
+        }
+        3 => {
+            let (filename, format) = get_output_file("maze.png");
+            let solutions = prefixed_filename(&filename, "solutions");
+            let limit = get_solution_limit() as usize;
+            report_save(
+                mazegenerator::create_and_save_maze_as(height, width, &filename, format).and_then(
+                    |maze| {
+                        mazegenerator::find_solutions(
+                            height, width, &maze, limit, &solutions, format,
+                        )
+                    },
+                ),
+            );
+        }
+        4 => {
+            let (filename, format) = get_output_file("maze.png");
+            let heatmap = prefixed_filename(&filename, "heatmap");
+            report_save(
+                mazegenerator::create_and_save_maze_as(height, width, &filename, format).and_then(
+                    |maze| mazegenerator::compare_solvers(height, width, &maze, &heatmap, format),
+                ),
+            );
+        }
+        5 => {
+            let (filename, format) = get_output_file("maze.png");
+            let distance = prefixed_filename(&filename, "distance");
+            let gradient = get_gradient();
+            report_save(
+                mazegenerator::create_and_save_maze_as(height, width, &filename, format).and_then(
+                    |maze| {
+                        mazegenerator::save_distance_map(
+                            height, width, &maze, &gradient, &distance, format,
+                        )
+                    },
+                ),
+            );
+        }
+        6 => {
+            let (filename, format) = get_output_file("maze.png");
+            let options = get_animation_options();
+            report_save(
+                mazegenerator::create_and_animate_maze(height, width, &options, &filename, format)
+                    .and_then(|maze| {
+                        mazegenerator::animate_solve_maze(height, width, &maze, &options)
+                    }),
+            );
+        }
+        7 => {
+            let (filename, format) = get_output_file("maze.png");
+            let svg = Path::new(&filename).with_extension("svg");
+            let svg = svg.to_string_lossy();
+            let solve = get_drawn_solution();
+            let options = mazegenerator::SvgOptions::new();
+            report_save(
+                mazegenerator::create_and_save_maze_as(height, width, &filename, format).and_then(
+                    |maze| {
+                        mazegenerator::save_svg_maze(height, width, &maze, solve, &options, &svg)
+                    },
+                ),
+            );
+        }
+        8 => {
+            let (filename, format) = get_output_file("maze.png");
+            let rendered = prefixed_filename(&filename, "rendered");
+            let options = get_render_options();
+            let solve = get_drawn_solution();
+            report_save(
+                mazegenerator::create_and_save_maze_as(height, width, &filename, format).and_then(
+                    |maze| {
+                        mazegenerator::render_maze(
+                            height, width, &maze, solve, &options, &rendered, format,
+                        )
+                    },
+                ),
+            );
+        }
+        9 => {
+            let (filename, format) = get_output_file("maze.png");
+            let style = get_text_style();
+            let solve = get_drawn_solution();
+            match mazegenerator::create_and_save_maze_as(height, width, &filename, format) {
+                Ok(maze) => mazegenerator::print_maze(height, width, &maze, solve, &style),
+                Err(err) => report_save(Err(err)),
+            }
+        }
+        11 => {
+            let (image, format) = get_output_file("maze.png");
+            let text = get_maze_file("text", "maze.txt");
+            let saved = mazegenerator::create_and_save_maze_as(height, width, &image, format)
+                .and_then(|maze| mazegenerator::save_maze_text(height, width, &maze, &text));
+            if let Err(err) = saved {
+                println!("Unable to save {}: {}", text, err);
+            }
+        }
+        12 => {
+            let (image, format) = get_output_file("maze.png");
+            let archive = get_maze_file("binary maze", "maze.mzb");
+            if let Err(err) =
+                mazegenerator::create_and_archive_maze(height, width, &archive, &image, format)
+            {
+                println!("Unable to save {}: {}", archive, err);
+            }
+        }
+        13 => {
+            let json = get_maze_file("JSON maze", "maze.json");
+            if let Err(err) = mazegenerator::create_and_export_maze(height, width, &json) {
+                println!("Unable to save {}: {}", json, err);
+            }
+        }
+        14 => {
+            let (filename, format) = get_output_file("maze.png");
+            match mazegenerator::create_and_save_maze_as(height, width, &filename, format) {
+                Ok(maze) => {
+                    mazegenerator::check_maze(height, width, &maze);
+                }
+                Err(err) => report_save(Err(err)),
+            }
+        }
+        15 => mazegenerator::compare_generators(height, width),
+        16 => {
+            let target = match get_target() {
+                Ok(target) => target,
+                Err(message) => return println!("Unable to aim for that target: {}", message),
+            };
+            let seed = get_seed();
+            let tune = get_braid_tuning();
+            let (image, format) = get_output_file("maze.png");
+            let archive = get_maze_file("binary maze", "maze.mzb");
+            if let Err(err) = mazegenerator::create_maze_to_target(
+                height, width, &target, seed, tune, &archive, &image, format,
+            ) {
+                println!("Unable to save {}: {}", archive, err);
+            }
+        }
+        18 => {
+            let (filename, format) = get_output_file("hex-maze.png");
+            let options = get_render_options();
+            let solve = get_drawn_solution();
+            let created =
+                mazegenerator::create_hex_maze(width, height, solve, &options, &filename, format);
+            report_save(created.map(|_| ()));
+        }
+        20 => {
+            let (filename, format) = get_output_file("triangle-maze.png");
+            let options = get_render_options();
+            let solve = get_drawn_solution();
+            let created = mazegenerator::create_triangle_maze(
+                width, height, solve, &options, &filename, format,
+            );
+            report_save(created.map(|_| ()));
+        }
+        21 => {
+            let levels = get_levels();
+            let (filename, format) = get_output_file("level-maze.png");
+            let options = get_render_options();
+            let solve = get_drawn_solution();
+            let created = mazegenerator::create_level_maze(
+                width, height, levels, solve, &options, &filename, format,
+            );
+            report_save(created.map(|_| ()));
+        }
+        22 => {
+            let (filename, format) = get_output_file("weave-maze.png");
+            let options = get_render_options();
+            let solve = get_drawn_solution();
+            let created =
+                mazegenerator::create_weave_maze(width, height, solve, &options, &filename, format);
+            report_save(created.map(|_| ()));
         }
         _ => {}
     }
 }
 
+// Prints why the maze or one of its images could not be saved
+fn report_save(saved: io::Result<()>) {
+    if let Err(err) = saved {
+        println!("Unable to save the maze: {}", err);
+    }
+}
+
+// Picks how to read the maze from the end of the filename
+fn load_maze(filename: &str) -> Result<mazegenerator::LoadedMaze, mazegenerator::LoadError> {
+    if filename.ends_with(".txt") {
+        mazegenerator::load_maze_text(filename)
+    } else if filename.ends_with(".mzb") {
+        mazegenerator::load_maze_binary(filename).map(|(maze, _)| maze)
+    } else if filename.ends_with(".json") {
+        mazegenerator::load_maze_json(filename).map(|(maze, _)| maze)
+    } else {
+        mazegenerator::load_maze_image(filename, get_threshold())
+    }
+}
+
 fn get_mode_option() -> u32 {
     let mut input = String::new();
 
     println!("Do you want to:");
     println!("1. Generate a maze and solve it.");
     println!("2. Generate a maze.");
+    println!("3. Generate a maze and find its solutions.");
+    println!("4. Generate a maze and compare the solvers.");
+    println!("5. Generate a maze and colour it by distance.");
+    println!("6. Generate and solve a maze, recording animations.");
+    println!("7. Generate a maze and save it as an SVG.");
+    println!("8. Generate a maze and render it at a larger size.");
+    println!("9. Generate a maze and print it as text.");
+    println!("10. Load a maze from an image, text, binary or JSON file and solve it.");
+    println!("11. Generate a maze and save it as text.");
+    println!("12. Generate a maze and archive it in the binary format.");
+    println!("13. Generate a maze and export it with its solutions as JSON.");
+    println!("14. Generate a maze and check that it is valid and perfect.");
+    println!("15. Compare statistics of mazes made by each generator.");
+    println!("16. Generate a maze to a target difficulty.");
+    println!("17. Generate a maze in the shape of a mask image or text file.");
+    println!("18. Generate a maze on a grid of hexagons.");
+    println!("19. Generate a circular maze.");
+    println!("20. Generate a maze on a grid of triangles.");
+    println!("21. Generate a maze over several levels joined by stairs.");
+    println!("22. Generate a weave maze with passages tunnelling under each other.");
     io::stdin()
         .read_line(&mut input)
         .expect("get_mode_option -- unable to parse console input!");
@@ -60,6 +349,363 @@ fn get_maze_size() -> (u32, u32) {
     (height, width)
 }
 
+fn get_rings() -> u32 {
+    let mut input = String::new();
+
+    println!("How many rings should the maze have?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_rings -- unable to parse console input!");
+
+    parse_u32(&input).max(1)
+}
+
+fn get_levels() -> u32 {
+    let mut input = String::new();
+
+    println!("How many levels should the maze have?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_levels -- unable to parse console input!");
+
+    parse_u32(&input).max(1)
+}
+
+fn get_output_file(default: &str) -> (String, mazegenerator::ImageFormat) {
+    let mut input = String::new();
+
+    println!(
+        "Where do you want to save the image? Leave blank for {}.",
+        default
+    );
+    println!("PNG, JPEG, BMP, PPM and GIF files can be written.");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_output_file -- unable to parse console input!");
+
+    let filename = match input.trim() {
+        "" => String::from(default),
+        name => String::from(name),
+    };
+
+    match mazegenerator::ImageFormat::from_filename(&filename) {
+        Some(format) => (filename, format),
+        None => {
+            println!(
+                "unrecognised image type for {}, saving it as a PNG",
+                filename
+            );
+            (filename, mazegenerator::ImageFormat::Png)
+        }
+    }
+}
+
+// Asks where to save the maze in one of the maze file formats, such as text
+fn get_maze_file(kind: &str, default: &str) -> String {
+    let mut input = String::new();
+
+    println!(
+        "Where do you want to save the {}? Leave blank for {}.",
+        kind, default
+    );
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_maze_file -- unable to parse console input!");
+
+    match input.trim() {
+        "" => String::from(default),
+        name => String::from(name),
+    }
+}
+
+// Puts a prefix such as solved- in front of the file name, keeping it in the
+// same directory
+fn prefixed_filename(filename: &str, prefix: &str) -> String {
+    let path = Path::new(filename);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(filename);
+
+    path.with_file_name(format!("{}-{}", prefix, name))
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn get_mask_file() -> String {
+    let mut input = String::new();
+
+    println!("Which image or .txt file has the shape of the maze?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_mask_file -- unable to parse console input!");
+
+    String::from(input.trim())
+}
+
+fn get_input_file() -> String {
+    let mut input = String::new();
+
+    println!("Which image, .txt, .mzb or .json file do you want to load?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_input_file -- unable to parse console input!");
+
+    String::from(input.trim())
+}
+
+fn get_threshold() -> u8 {
+    let mut input = String::new();
+
+    println!("How bright (0 to 255) does a pixel need to be to count as a passage?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_threshold -- unable to parse console input!");
+
+    parse_u32(&input).min(255) as u8
+}
+
+fn get_mask_threshold() -> u8 {
+    let mut input = String::new();
+
+    println!("How dark (0 to 255) does a pixel need to be to count as inside the maze?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_mask_threshold -- unable to parse console input!");
+
+    // Pixels darker than the threshold are inside
+    parse_u32(&input).saturating_add(1).min(255) as u8
+}
+
+fn get_target() -> Result<mazegenerator::Target, String> {
+    let mut input = String::new();
+
+    println!("What do you want to aim for?");
+    println!("1. Difficulty score,");
+    println!("2. Solution length,");
+    println!("3. Decision points on the solution,");
+    println!("4. Dead end depth,");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_target -- unable to parse console input!");
+
+    let metric = match parse_u32(&input) {
+        2 => mazegenerator::Metric::SolutionLength,
+        3 => mazegenerator::Metric::DecisionPoints,
+        4 => mazegenerator::Metric::DeadEndDepth,
+        _ => mazegenerator::Metric::Difficulty,
+    };
+
+    let mut input = String::new();
+
+    println!("Please enter the lowest value to accept.");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_target -- unable to parse console input!");
+
+    let low = parse_f64(&input);
+
+    let mut input = String::new();
+
+    println!("Please enter the highest value to accept.");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_target -- unable to parse console input!");
+
+    let high = parse_f64(&input);
+
+    mazegenerator::Target::new(metric, low, high)
+}
+
+fn get_seed() -> u64 {
+    let mut input = String::new();
+
+    println!("Which seed do you want to start from?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_seed -- unable to parse console input!");
+
+    parse_u64(&input)
+}
+
+fn get_braid_tuning() -> bool {
+    let mut input = String::new();
+
+    println!("Can dead ends be opened up into loops to hit the target?");
+    println!("1. Yes,");
+    println!("2. No,");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_braid_tuning -- unable to parse console input!");
+
+    parse_u32(&input) == 1
+}
+
+fn get_solution_limit() -> u32 {
+    let mut input = String::new();
+
+    println!("How many solutions do you want to look for?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_solution_limit -- unable to parse console input!");
+
+    parse_u32(&input)
+}
+
+fn get_gradient() -> mazegenerator::Gradient {
+    let mut input = String::new();
+
+    println!("Which colours do you want to use?");
+    println!("1. Viridis,");
+    println!("2. Rainbow,");
+    println!("3. White to blue,");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_gradient -- unable to parse console input!");
+
+    match parse_u32(&input) {
+        1 => mazegenerator::Gradient::Viridis,
+        2 => mazegenerator::Gradient::Rainbow,
+        3 => mazegenerator::Gradient::TwoColour([255, 255, 255], [20, 40, 160]),
+        option => {
+            println!("unrecognised option {}, defaulting to Viridis", option);
+            mazegenerator::Gradient::Viridis
+        }
+    }
+}
+
+fn get_animation_options() -> mazegenerator::AnimationOptions {
+    let mut input = String::new();
+
+    println!("Which animation format do you want to use?");
+    println!("1. GIF,");
+    println!("2. APNG,");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_animation_options -- unable to parse console input!");
+
+    let format = match parse_u32(&input) {
+        1 => mazegenerator::AnimationFormat::Gif,
+        2 => mazegenerator::AnimationFormat::Apng,
+        option => {
+            println!("unrecognised option {}, defaulting to GIF", option);
+            mazegenerator::AnimationFormat::Gif
+        }
+    };
+
+    let mut input = String::new();
+
+    println!("How many steps should each frame of the animation show?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_animation_options -- unable to parse console input!");
+
+    let mut options = mazegenerator::AnimationOptions::new(format);
+    options.frame_skip = parse_u32(&input) as usize;
+
+    let mut input = String::new();
+
+    println!("How many milliseconds should each frame be shown for?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_animation_options -- unable to parse console input!");
+
+    options.delay = parse_u32(&input).min(u32::from(u16::MAX)) as u16;
+
+    let mut input = String::new();
+
+    println!("How many pixels across should each maze pixel be drawn?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_animation_options -- unable to parse console input!");
+
+    options.scale = parse_u32(&input).max(1);
+
+    options
+}
+
+fn get_render_options() -> mazegenerator::RenderOptions {
+    let mut input = String::new();
+
+    println!("Which theme do you want to use?");
+    println!("1. Classic,");
+    println!("2. Dark,");
+    println!("3. Blueprint,");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_render_options -- unable to parse console input!");
+
+    let theme = match parse_u32(&input) {
+        1 => mazegenerator::Theme::Classic,
+        2 => mazegenerator::Theme::Dark,
+        3 => mazegenerator::Theme::Blueprint,
+        option => {
+            println!("unrecognised option {}, defaulting to Classic", option);
+            mazegenerator::Theme::Classic
+        }
+    };
+
+    let mut options = mazegenerator::RenderOptions::new(theme);
+
+    let mut input = String::new();
+
+    println!("How many pixels wide should each passage be?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_render_options -- unable to parse console input!");
+
+    options.passage_width = parse_u32(&input).max(1);
+
+    let mut input = String::new();
+
+    println!("How many pixels thick should each wall be?");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_render_options -- unable to parse console input!");
+
+    options.wall_thickness = parse_u32(&input).max(1);
+
+    options
+}
+
+fn get_text_style() -> mazegenerator::TextStyle {
+    let mut input = String::new();
+
+    println!("Which characters do you want to use?");
+    println!("1. ASCII,");
+    println!("2. Unicode box drawing,");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_text_style -- unable to parse console input!");
+
+    match parse_u32(&input) {
+        1 => mazegenerator::TextStyle::Ascii,
+        2 => mazegenerator::TextStyle::Unicode,
+        option => {
+            println!("unrecognised option {}, defaulting to ASCII", option);
+            mazegenerator::TextStyle::Ascii
+        }
+    }
+}
+
+fn get_drawn_solution() -> bool {
+    println!("Do you want to draw the solution?");
+    println!("1. Yes");
+    println!("2. No");
+
+    let mut input = String::new();
+
+    io::stdin()
+        .read_line(&mut input)
+        .expect("get_drawn_solution -- unable to parse console input!");
+
+    match parse_u32(&input) {
+        1 => true,
+        2 => false,
+        _ => false,
+    }
+}
+
 fn get_solver_retry() -> bool {
     println!("Do you want to run another solver?");
     println!("1. Yes");
@@ -78,14 +724,36 @@ fn get_solver_retry() -> bool {
     }
 }
 
-fn parse_u32(text: &String) -> u32 {
-    let option = match text.trim().parse::<u32>() {
+fn parse_u32(text: &str) -> u32 {
+    match text.trim().parse::<u32>() {
         Ok(num) => num,
         Err(err) => {
             println!("Please enter a number! Error: {}", err);
             std::process::exit(1);
         }
-    };
+    }
+}
 
-    option
+fn parse_u64(text: &str) -> u64 {
+    match text.trim().parse::<u64>() {
+        Ok(num) => num,
+        Err(err) => {
+            println!("Please enter a number! Error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn parse_f64(text: &str) -> f64 {
+    match text.trim().parse::<f64>() {
+        Ok(num) if num.is_finite() => num,
+        Ok(num) => {
+            println!("Please enter a finite number, not {}!", num);
+            std::process::exit(1);
+        }
+        Err(err) => {
+            println!("Please enter a number! Error: {}", err);
+            std::process::exit(1);
+        }
+    }
 }
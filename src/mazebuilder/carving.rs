@@ -0,0 +1,22 @@
+/// The order a generator opened up the passages of a maze, so that it can be
+/// played back later.
+pub struct Carving {
+    /// Passages that were already open before the generator started
+    pub initial: Vec<(u32, u32)>,
+    /// Passages in the order they were carved out
+    pub steps: Vec<(u32, u32)>,
+}
+
+impl Carving {
+    pub fn new() -> Carving {
+        Carving {
+            initial: Vec::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Records that a passage was carved out.
+    pub fn carve(&mut self, node: (u32, u32)) {
+        self.steps.push(node);
+    }
+}
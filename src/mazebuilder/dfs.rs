@@ -1,93 +1,71 @@
-use std::collections::HashSet;
-use rand::distributions::{IndependentSample, Range};
-
-use super::maze::{Cell, Direction, Maze};
-
-pub fn recursive_backtracker(height: u32, width: u32) -> HashSet<(u32, u32)> {
-    // Generate a maze with the given width and height
-    let maze = Maze::new(height, width);
-
-    // Stack for backtracking
-    let mut stack: Vec<&Cell> = Vec::new();
-
-    // Visted Cells
-    let mut visited: HashSet<(u32, u32)> = HashSet::new();
-
-    // Get the inital cell and mark it as visited.
-    let mut current = maze.get_cell(1, 1).unwrap();
-    visited.insert((current.x, current.y));
-
-    println!("Generating Maze Paths...");
-    // While there are unvisited cells --
-    while visited.len() != (height * width) as usize {
-        // Get the unvisted neighbours for the current cell
-        let neighbours = get_cell_neighbours(&maze, current, &visited);
-
-        // If the cell has any neighbours that have not been visted
-        if neighbours.len() > 0 {
-            // pick a random neighbour
-            let (chosen, dir) = pick_random_neighbour(&neighbours);
-
-            // Push the current cell to the stack
-            stack.push(current);
-
-            // Remove the wall between the current and chosen cell
-            // check the direction of the chosen cell to see where
-            // we have moved. Then go back one cell as this is the wall.
-            let wall = match dir {
-                Direction::North => maze.get_cell(current.x, current.y - 1),
-                Direction::East => maze.get_cell(current.x + 1, current.y),
-                Direction::South => maze.get_cell(current.x, current.y + 1),
-                Direction::West => maze.get_cell(current.x - 1, current.y),
-            }.unwrap();
-
-            visited.insert((wall.x, wall.y));
-
-            // Make the chosen cell the new current cell and mark as visted
-            current = chosen;
-            visited.insert((current.x, current.y));
-        } else {
-            // If the stack is not empty
-            if stack.len() > 0 {
-                // pop a cell from the stack and make it the current one
-                current = stack.pop().unwrap();
-            } else {
-                // We are done!
-                break;
-            }
+use rand::Rng;
+
+use grids::{pair, Grid, GridMaze};
+
+use super::pick;
+
+/// Carves a maze with a depth first walk, calling `step` with the cell it
+/// carved from and the cell it carved into each time it knocks down a wall.
+pub fn recursive_backtracker<G: Grid, R: Rng, F: FnMut(usize, usize)>(
+    maze: &mut GridMaze<G>,
+    rng: &mut R,
+    step: &mut F,
+) {
+    let mut visited = vec![false; maze.grid.cell_count()];
+    let mut stack: Vec<usize> = Vec::new();
+
+    // A grid can be in separate parts, so start again from the first cell
+    // of each part that hasn't been reached
+    for first in 0..visited.len() {
+        if visited[first] {
+            continue;
         }
-    }
-    println!("Path generation finished.");
-
-    // Return the path map of the maze so that it can be converted into a image
-    visited
-}
 
-fn get_cell_neighbours<'a>(
-    maze: &'a Maze,
-    current: &Cell,
-    visited: &HashSet<(u32, u32)>,
-) -> Vec<(&'a Cell, Direction)> {
-    let mut neighbours = maze.get_cell_neighbours(current);
-    neighbours.retain(|c| {
-        let mut test = true;
-        let cell = c.0;
+        visited[first] = true;
+        stack.push(first);
+
+        while let Some(&current) = stack.last() {
+            // Tunnelling under a passage that has already been carved is
+            // another way to reach somewhere new
+            let tunnels: Vec<usize> = maze
+                .grid
+                .tunnels(current)
+                .into_iter()
+                .filter(|&(under, beyond)| !visited[beyond] && can_tunnel(maze, current, under))
+                .map(|(_, beyond)| beyond)
+                .collect();
+            let unvisited: Vec<usize> = maze
+                .grid
+                .neighbours(current)
+                .into_iter()
+                .filter(|&cell| !visited[cell])
+                .chain(tunnels)
+                .collect();
+
+            // Nothing new from here, so backtrack
+            if unvisited.is_empty() {
+                stack.pop();
+                continue;
+            }
 
-        if visited.contains(&(cell.x, cell.y)) {
-            test = false;
+            let chosen = pick(&unvisited, rng);
+            maze.link(current, chosen);
+            step(current, chosen);
+            visited[chosen] = true;
+            stack.push(chosen);
         }
-
-        test
-    });
-
-    neighbours
+    }
 }
 
-fn pick_random_neighbour<'a>(neighbours: &Vec<(&'a Cell, Direction)>) -> ((&'a Cell, Direction)) {
-    let between = Range::new(0, neighbours.len());
-    let mut rng = super::rand::thread_rng();
-    let random = between.ind_sample(&mut rng) as usize;
-    let chosen = neighbours[random];
-
-    (chosen)
+// A tunnel can go under a cell that is a straight passage across the way the
+// tunnel is heading
+fn can_tunnel<G: Grid>(maze: &GridMaze<G>, from: usize, under: usize) -> bool {
+    let passages = maze.passages(under);
+
+    passages.len() == 2
+        && !passages.contains(&from)
+        && maze
+            .grid
+            .crossings(under)
+            .contains(&pair(passages[0], passages[1]))
 }
@@ -0,0 +1,73 @@
+/// The cells of the grid that are part of the maze, so that mazes can be made
+/// in the shape of a picture or some text.
+///
+/// Cells are counted in columns and rows, so cell `(column, row)` is the maze
+/// pixel `(2 * column + 1, 2 * row + 1)`.
+#[derive(Clone)]
+pub struct Mask {
+    columns: u32,
+    rows: u32,
+    cells: Vec<bool>,
+}
+
+impl Mask {
+    /// A mask with every cell part of the maze.
+    pub fn new(columns: u32, rows: u32) -> Mask {
+        Mask {
+            columns,
+            rows,
+            cells: vec![true; (columns * rows) as usize],
+        }
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Height and width of the mazes made with this mask.
+    pub fn size(&self) -> (u32, u32) {
+        (2 * self.rows + 1, 2 * self.columns + 1)
+    }
+
+    pub fn set(&mut self, column: u32, row: u32, inside: bool) {
+        if column < self.columns && row < self.rows {
+            self.cells[(row * self.columns + column) as usize] = inside;
+        }
+    }
+
+    pub fn is_inside(&self, column: u32, row: u32) -> bool {
+        column < self.columns
+            && row < self.rows
+            && self.cells[(row * self.columns + column) as usize]
+    }
+
+    /// Whether the cell at a maze pixel is part of the maze. Pixels between
+    /// cells aren't cells, so they never are.
+    pub fn contains(&self, node: (u32, u32)) -> bool {
+        node.0 % 2 == 1 && node.1 % 2 == 1 && self.is_inside(node.0 / 2, node.1 / 2)
+    }
+
+    /// Number of cells that are part of the maze.
+    pub fn count(&self) -> usize {
+        self.cells.iter().filter(|&&inside| inside).count()
+    }
+
+    /// Maze pixels of the cells inside the mask, row by row from the top left.
+    pub fn nodes(&self) -> Vec<(u32, u32)> {
+        let mut nodes = Vec::with_capacity(self.count());
+
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                if self.is_inside(column, row) {
+                    nodes.push((2 * column + 1, 2 * row + 1));
+                }
+            }
+        }
+
+        nodes
+    }
+}
@@ -1,32 +1,265 @@
 extern crate rand;
+mod carving;
 mod dfs;
 mod imperfect;
 mod kruskal;
-mod maze;
+mod mask;
 
 use std::collections::HashSet;
 
+use self::rand::distributions::{IndependentSample, Range};
+use self::rand::{Rng, SeedableRng, StdRng};
+
+use grids::{Grid, GridMaze, SquareGrid};
+
+pub use self::carving::Carving;
+pub use self::mask::Mask;
+
+/// Share of the dead ends that imperfect mazes open up into loops.
+pub const IMPERFECT_BRAID: f32 = 0.05;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy)]
 pub enum Generator {
     DFS { height: u32, width: u32 },
     Kruskal { height: u32, width: u32 },
 }
 
+/// Algorithms that can carve a maze out of any grid.
+#[derive(Clone, Copy)]
+pub enum Algorithm {
+    Dfs,
+    Kruskal,
+}
+
+impl Algorithm {
+    /// Short name for the algorithm, used to label saved mazes.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Algorithm::Dfs => "dfs",
+            Algorithm::Kruskal => "kruskal",
+        }
+    }
+}
+
+impl Generator {
+    /// Short name for the algorithm, used to label saved mazes.
+    pub fn name(&self) -> &'static str {
+        self.algorithm().name()
+    }
+
+    /// The algorithm, whatever the size.
+    pub fn algorithm(&self) -> Algorithm {
+        match *self {
+            Generator::DFS { .. } => Algorithm::Dfs,
+            Generator::Kruskal { .. } => Algorithm::Kruskal,
+        }
+    }
+
+    /// Height and width of the mazes it makes.
+    pub fn size(&self) -> (u32, u32) {
+        match *self {
+            Generator::DFS { height, width } | Generator::Kruskal { height, width } => {
+                (height, width)
+            }
+        }
+    }
+}
+
 pub fn generate_maze(gen: Generator, imperfect: bool) -> HashSet<(u32, u32)> {
-    let mut maze = match gen {
-        Generator::DFS { height, width } => dfs::recursive_backtracker(height, width),
-        Generator::Kruskal { height, width } => kruskal::kruskal(height, width),
-    };
+    generate_maze_recorded(gen, imperfect).0
+}
+
+/// Generates a maze, also returning the order its passages were carved out.
+pub fn generate_maze_recorded(gen: Generator, imperfect: bool) -> (HashSet<(u32, u32)>, Carving) {
+    let braid = if imperfect { IMPERFECT_BRAID } else { 0.0 };
+    let mut carving = Carving::new();
+
+    let mask = full_mask(gen);
+    let mut rng = rand::thread_rng();
+    let maze = build(gen, &mask, braid, &mut carving, &mut rng, true);
+
+    (maze, carving)
+}
+
+/// Generates a maze that only fills the cells inside the mask, which has to
+/// be the same size as the generator's mazes.
+///
+/// If the mask splits the grid into separate parts, each part gets a maze of
+/// its own.
+pub fn generate_masked_maze(gen: Generator, mask: &Mask, imperfect: bool) -> HashSet<(u32, u32)> {
+    assert!(
+        gen.size() == mask.size(),
+        "generate_masked_maze -- the mask is a different size to the maze!"
+    );
 
-    if imperfect {
-        maze = imperfect::generate(maze);
+    let braid = if imperfect { IMPERFECT_BRAID } else { 0.0 };
+
+    let mut rng = rand::thread_rng();
+    build(gen, mask, braid, &mut Carving::new(), &mut rng, true)
+}
+
+/// Generates a maze from a seed, so the same seed always gives the same maze.
+/// `braid` is the share of dead ends, from 0 to 1, that are opened up into loops.
+///
+/// Nothing is printed along the way, so searches can try thousands of seeds.
+pub fn generate_maze_seeded(gen: Generator, braid: f32, seed: u64) -> HashSet<(u32, u32)> {
+    let mut rng = StdRng::from_seed(&[seed as usize, (seed >> 32) as usize][..]);
+
+    let mask = full_mask(gen);
+    build(gen, &mask, braid, &mut Carving::new(), &mut rng, false)
+}
+
+// Every cell of the generator's grid
+fn full_mask(gen: Generator) -> Mask {
+    let (height, width) = gen.size();
+    Mask::new(width / 2, height / 2)
+}
+
+/// Carves a maze out of any grid. `braid` is the share of dead ends, from 0
+/// to 1, that are opened up into loops afterwards.
+pub fn generate_grid<G: Grid, R: Rng>(
+    grid: G,
+    algorithm: Algorithm,
+    braid: f32,
+    rng: &mut R,
+) -> GridMaze<G> {
+    generate_grid_recorded(grid, algorithm, braid, rng, |_, _| {})
+}
+
+/// Carves a maze out of any grid like `generate_grid`, calling `step` with
+/// the cell carved from and the cell carved into each time a wall comes down.
+pub fn generate_grid_recorded<G: Grid, R: Rng, F: FnMut(usize, usize)>(
+    grid: G,
+    algorithm: Algorithm,
+    braid: f32,
+    rng: &mut R,
+    mut step: F,
+) -> GridMaze<G> {
+    let mut maze = GridMaze::new(grid);
+
+    match algorithm {
+        Algorithm::Dfs => dfs::recursive_backtracker(&mut maze, rng, &mut step),
+        Algorithm::Kruskal => kruskal::kruskal(&mut maze, rng, &mut step),
+    }
+
+    if braid > 0.0 {
+        imperfect::generate(&mut maze, braid, rng, &mut step);
     }
 
     maze
 }
 
+// Carves the maze out of a square grid of the mask's cells, then opens up
+// the maze pixels in the order the generator got to them. Prints how it is
+// getting on when verbose.
+fn build<R: Rng>(
+    gen: Generator,
+    mask: &Mask,
+    braid: f32,
+    carving: &mut Carving,
+    rng: &mut R,
+    verbose: bool,
+) -> HashSet<(u32, u32)> {
+    if verbose {
+        println!("Generating Maze Paths with {}...", gen.name());
+    }
+
+    let grid = SquareGrid::masked(mask);
+    let mut steps: Vec<(usize, usize)> = Vec::new();
+    let maze = generate_grid_recorded(grid, gen.algorithm(), braid, rng, |from, to| {
+        steps.push((from, to))
+    });
+    let pixel = |cell: usize| {
+        let (column, row) = maze.grid.position(cell);
+        (2 * column + 1, 2 * row + 1)
+    };
+
+    // Kruskal's algorithm starts out with every cell on its own, where the
+    // others reach each cell as they go
+    let mut passages: HashSet<(u32, u32)> = HashSet::new();
+    if let Algorithm::Kruskal = gen.algorithm() {
+        carving.initial = mask.nodes();
+        passages.extend(mask.nodes());
+    }
+
+    // Open up each wall in the order the generator knocked it down, going
+    // from the cell it was carved from
+    for (from, to) in steps {
+        let (from, to) = (pixel(from), pixel(to));
+        let wall = ((from.0 + to.0) / 2, (from.1 + to.1) / 2);
+
+        for &node in &[from, wall, to] {
+            if passages.insert(node) {
+                carving.carve(node);
+            }
+        }
+    }
+
+    // Parts of the mask only one cell big never get a passage
+    for node in mask.nodes() {
+        if passages.insert(node) {
+            carving.carve(node);
+        }
+    }
+
+    if verbose {
+        println!("Path generation finished.");
+    }
+
+    passages
+}
+
+fn pick<R: Rng>(cells: &[usize], rng: &mut R) -> usize {
+    let between = Range::new(0, cells.len());
+
+    cells[between.ind_sample(rng)]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use analysis::validate;
+    use grids::HexGrid;
+    use std::collections::VecDeque;
+    use testing::{check_every_grid, GridCheck};
+
+    // Even sizes leave the last row and column of cells on the edge, so
+    // only check that every cell is joined up without any loops
+    fn assert_spanning_tree(maze: &HashSet<(u32, u32)>, height: u32, width: u32) {
+        let validation = validate((1, 1), height, width, maze);
+
+        assert!(validation.is_connected());
+        assert!(validation.cycles.is_empty());
+        assert_eq!(validation.links + 1, validation.passages);
+    }
+
+    fn dfs_maze(height: u32, width: u32) -> HashSet<(u32, u32)> {
+        generate_maze(Generator::DFS { height, width }, false)
+    }
+
+    fn kruskal_maze(height: u32, width: u32) -> HashSet<(u32, u32)> {
+        generate_maze(Generator::Kruskal { height, width }, false)
+    }
+
+    // Every cell can be reached from the first one
+    fn is_connected<G: Grid>(maze: &GridMaze<G>) -> bool {
+        let mut seen = vec![false; maze.grid.cell_count()];
+        let mut queue = VecDeque::new();
+        seen[0] = true;
+        queue.push_back(0);
+
+        while let Some(cell) = queue.pop_front() {
+            for next in maze.passages(cell) {
+                if !seen[next] {
+                    seen[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        seen.iter().all(|&seen| seen)
+    }
 
     #[test]
     fn test_generate_maze_selector() {
@@ -45,52 +278,225 @@ mod tests {
         assert_eq!(result1.len(), result2.len());
     }
 
+    #[test]
+    fn test_seeded_mazes_repeat() {
+        let dfs = Generator::DFS {
+            height: 31,
+            width: 41,
+        };
+        let kruskal = Generator::Kruskal {
+            height: 31,
+            width: 41,
+        };
+
+        for &gen in &[dfs, kruskal] {
+            assert!(generate_maze_seeded(gen, 0.0, 7) == generate_maze_seeded(gen, 0.0, 7));
+            assert!(generate_maze_seeded(gen, 0.0, 7) != generate_maze_seeded(gen, 0.0, 8));
+            assert!(generate_maze_seeded(gen, 0.3, 7) == generate_maze_seeded(gen, 0.3, 7));
+
+            // Opening up dead ends only ever adds passages
+            let perfect = generate_maze_seeded(gen, 0.0, 9);
+            assert!(validate((1, 1), 31, 41, &perfect).is_perfect());
+            assert!(generate_maze_seeded(gen, 0.5, 9).len() > perfect.len());
+        }
+    }
+
+    #[test]
+    fn test_carving_matches_maze() {
+        let gen = Generator::Kruskal {
+            height: 21,
+            width: 21,
+        };
+        let (maze, carving) = generate_maze_recorded(gen, true);
+
+        // Every passage was either there from the start or carved exactly once
+        let mut replayed: HashSet<(u32, u32)> = carving.initial.iter().cloned().collect();
+        for step in &carving.steps {
+            assert!(replayed.insert(*step));
+        }
+        assert_eq!(replayed, maze);
+
+        let gen = Generator::DFS {
+            height: 21,
+            width: 21,
+        };
+        let (maze, carving) = generate_maze_recorded(gen, false);
+        assert!(carving.initial.is_empty());
+        assert_eq!(carving.steps.len(), maze.len());
+    }
+
     #[test]
     fn test_dfs_10_10() {
-        let test1 = dfs::recursive_backtracker(10, 10);
+        let test1 = dfs_maze(10, 10);
         assert_eq!(test1.len(), 49);
+        assert_spanning_tree(&test1, 10, 10);
     }
 
     #[test]
     fn test_dfs_15_23() {
-        let test2 = dfs::recursive_backtracker(15, 23);
+        let test2 = dfs_maze(15, 23);
         assert_eq!(test2.len(), 153);
+        assert_spanning_tree(&test2, 15, 23);
     }
 
     #[test]
     fn test_dfs_100_100() {
-        let test3 = dfs::recursive_backtracker(100, 100);
+        let test3 = dfs_maze(100, 100);
         assert_eq!(test3.len(), 4999);
+        assert_spanning_tree(&test3, 100, 100);
     }
 
     #[test]
     fn test_kruskal_10_10() {
-        let test1 = kruskal::kruskal(10, 10);
+        let test1 = kruskal_maze(10, 10);
         assert_eq!(test1.len(), 49);
+        assert_spanning_tree(&test1, 10, 10);
     }
 
     #[test]
     fn test_kruskal_15_23() {
-        let test2 = kruskal::kruskal(15, 23);
+        let test2 = kruskal_maze(15, 23);
         assert_eq!(test2.len(), 153);
+        assert_spanning_tree(&test2, 15, 23);
     }
 
     #[test]
     fn test_kruskal_100_100() {
-        let test3 = kruskal::kruskal(100, 100);
+        let test3 = kruskal_maze(100, 100);
         assert_eq!(test3.len(), 4999);
+        assert_spanning_tree(&test3, 100, 100);
+    }
+
+    #[test]
+    fn test_odd_sizes_are_perfect() {
+        for &(height, width) in &[(11, 11), (15, 23), (101, 51)] {
+            let maze = dfs_maze(height, width);
+            assert!(validate((1, 1), height, width, &maze).is_perfect());
+
+            let maze = kruskal_maze(height, width);
+            assert!(validate((1, 1), height, width, &maze).is_perfect());
+        }
     }
 
     #[test]
     fn test_kruskal_imperfect() {
-        let maze = kruskal::kruskal(100, 100);
-        let _test = imperfect::generate(maze);
+        let test = generate_maze(
+            Generator::Kruskal {
+                height: 101,
+                width: 101,
+            },
+            true,
+        );
+
+        let validation = validate((1, 1), 101, 101, &test);
+        assert!(validation.is_valid());
+        assert!(!validation.cycles.is_empty());
     }
 
     #[test]
     fn test_dfs_imperfect() {
-        let maze = dfs::recursive_backtracker(100, 100);
-        let _test = imperfect::generate(maze);
+        let test = generate_maze(
+            Generator::DFS {
+                height: 101,
+                width: 101,
+            },
+            true,
+        );
+
+        let validation = validate((1, 1), 101, 101, &test);
+        assert!(validation.is_valid());
+        assert!(!validation.cycles.is_empty());
+    }
+
+    #[test]
+    fn test_masked_mazes() {
+        // A ring with a separate island in the middle
+        let mut mask = Mask::new(9, 9);
+        for row in 2..7 {
+            for column in 2..7 {
+                mask.set(column, row, false);
+            }
+        }
+        mask.set(4, 4, true);
+        mask.set(4, 5, true);
+
+        let (height, width) = mask.size();
+
+        for &gen in &[
+            Generator::DFS { height, width },
+            Generator::Kruskal { height, width },
+        ] {
+            let maze = generate_masked_maze(gen, &mask, false);
+
+            // Every cell inside the mask is open, and only walls between two
+            // of those cells are opened up
+            assert!(mask.nodes().iter().all(|cell| maze.contains(cell)));
+            for &(x, y) in &maze {
+                let (a, b) = match (x % 2, y % 2) {
+                    (1, 1) => ((x, y), (x, y)),
+                    (0, 1) => ((x - 1, y), (x + 1, y)),
+                    (1, 0) => ((x, y - 1), (x, y + 1)),
+                    _ => panic!("corner {:?} was opened up", (x, y)),
+                };
+                assert!(mask.contains(a) && mask.contains(b));
+            }
+
+            // The ring and the island are each a tree of their own
+            let validation = validate((1, 1), height, width, &maze);
+            assert!(validation.border_closed);
+            assert!(validation.cycles.is_empty());
+            assert_eq!(validation.isolated_regions.len(), 1);
+            assert_eq!(
+                validation.isolated_regions[0],
+                vec![(9, 9), (9, 10), (9, 11)]
+            );
+            assert_eq!(validation.links + 2, validation.passages);
+
+            let braided = generate_masked_maze(gen, &mask, true);
+            assert!(braided.len() >= maze.len());
+        }
     }
 
+    struct Perfect;
+
+    impl GridCheck for Perfect {
+        fn check<G: Grid>(&mut self, name: &str, maze: &GridMaze<G>) {
+            let cells = maze.grid.cell_count();
+
+            assert!(is_connected(maze), "{} maze left cells unreachable", name);
+            assert_eq!(maze.link_count() + 1, cells, "{} maze has a loop", name);
+        }
+    }
+
+    #[test]
+    fn test_every_grid_maze_is_perfect() {
+        check_every_grid(&mut Perfect);
+    }
+
+    #[test]
+    fn test_braid_is_held_to_every_dead_end() {
+        let gen = Generator::DFS {
+            height: 21,
+            width: 21,
+        };
+
+        // Asking for more than every dead end opens up every one of them
+        let all = generate_maze_seeded(gen, 1.0, 3);
+        assert!(generate_maze_seeded(gen, 4.0, 3) == all);
+        assert!(generate_maze_seeded(gen, -1.0, 3) == generate_maze_seeded(gen, 0.0, 3));
+
+        let mut rng = StdRng::from_seed(&[7usize][..]);
+        let mut maze = generate_grid(HexGrid::new(9, 7), Algorithm::Dfs, 0.0, &mut rng);
+        imperfect::generate(&mut maze, 2.5, &mut rng, &mut |_, _| {});
+        assert!(maze.link_count() + 1 > 9 * 7);
+    }
+
+    #[test]
+    fn test_braid_adds_loops() {
+        let mut rng = StdRng::from_seed(&[7usize][..]);
+        let maze = generate_grid(HexGrid::new(9, 7), Algorithm::Dfs, 1.0, &mut rng);
+
+        assert!(is_connected(&maze));
+        assert!(maze.link_count() + 1 > 9 * 7);
+    }
 }
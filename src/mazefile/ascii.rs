@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+
+use super::{check_ends, LoadError, LoadedMaze};
+
+pub fn parse(text: &str) -> Result<LoadedMaze, LoadError> {
+    let rows: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let height = rows.len() as u32;
+    let width = rows.first().map_or(0, |row| row.chars().count()) as u32;
+
+    if width < 3 || height < 3 {
+        return Err(LoadError::Invalid(format!(
+            "a {} by {} maze is too small to have a passage",
+            width, height
+        )));
+    }
+
+    let mut passages = HashSet::new();
+    let mut start = None;
+    let mut end = None;
+
+    for (y, row) in rows.iter().enumerate() {
+        let y = y as u32;
+
+        if row.chars().count() as u32 != width {
+            return Err(LoadError::Invalid(format!(
+                "line {} is {} characters long, expected {}",
+                y + 1,
+                row.chars().count(),
+                width
+            )));
+        }
+
+        for (x, c) in row.chars().enumerate() {
+            let position = (x as u32, y);
+
+            match c {
+                '#' => continue,
+                '.' | ' ' => {}
+                'S' => set_once(&mut start, position, 'S')?,
+                'E' => set_once(&mut end, position, 'E')?,
+                _ => {
+                    return Err(LoadError::Invalid(format!(
+                        "unexpected {:?} on line {} column {}",
+                        c,
+                        y + 1,
+                        x + 1
+                    )))
+                }
+            }
+
+            passages.insert(position);
+        }
+    }
+
+    check_ends(LoadedMaze {
+        height,
+        width,
+        passages,
+        start: start.unwrap_or((1, 1)),
+        end: end.unwrap_or((width - 2, height - 2)),
+    })
+}
+
+fn set_once(
+    marker: &mut Option<(u32, u32)>,
+    position: (u32, u32),
+    c: char,
+) -> Result<(), LoadError> {
+    if let Some(first) = *marker {
+        return Err(LoadError::Invalid(format!(
+            "{} is marked at both {:?} and {:?}",
+            c, first, position
+        )));
+    }
+
+    *marker = Some(position);
+    Ok(())
+}
+
+pub fn serialize(maze: &LoadedMaze) -> String {
+    let mut text = String::new();
+
+    for y in 0..maze.height {
+        for x in 0..maze.width {
+            let position = (x, y);
+
+            let c = if position == maze.start {
+                'S'
+            } else if position == maze.end {
+                'E'
+            } else if maze.passages.contains(&position) {
+                '.'
+            } else {
+                '#'
+            };
+
+            text.push(c);
+        }
+        text.push('\n');
+    }
+
+    text
+}
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+
+use crc;
+
+use super::{check_ends, LoadError, LoadedMaze, Metadata, Topology};
+
+// Every file starts with these bytes
+const MAGIC: &[u8; 4] = b"MAZE";
+const VERSION: u16 = 1;
+
+// Bits of the flags byte
+const HAS_SEED: u8 = 1;
+
+// Layout, with every number little endian:
+//
+//   magic          4 bytes   "MAZE"
+//   version        u16
+//   topology       u8
+//   flags          u8        bit 0 set if there is a seed
+//   width, height  u32 each
+//   start x, y     u32 each
+//   end x, y       u32 each
+//   seed           u64       zero if there isn't one
+//   generator      u16 length, then that many bytes of UTF-8
+//   walls          one bit per pixel, row by row from the top left, set for
+//                  walls, starting from the lowest bit of each byte
+//   checksum       u32       CRC-32 of everything before it
+pub fn write<W: Write>(w: &mut W, maze: &LoadedMaze, metadata: &Metadata) -> io::Result<()> {
+    let name = metadata.generator.as_bytes();
+    if name.len() > usize::from(u16::MAX) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "generator names can be at most 65535 bytes long",
+        ));
+    }
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.push(metadata.topology.code());
+    bytes.push(if metadata.seed.is_some() { HAS_SEED } else { 0 });
+
+    for value in &[
+        maze.width,
+        maze.height,
+        maze.start.0,
+        maze.start.1,
+        maze.end.0,
+        maze.end.1,
+    ] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    bytes.extend_from_slice(&metadata.seed.unwrap_or(0).to_le_bytes());
+    bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(name);
+
+    let pixels = u64::from(maze.width) * u64::from(maze.height);
+    let mut walls = vec![0u8; pixels.div_ceil(8) as usize];
+    for y in 0..maze.height {
+        for x in 0..maze.width {
+            if !maze.passages.contains(&(x, y)) {
+                let index = (u64::from(y) * u64::from(maze.width) + u64::from(x)) as usize;
+                walls[index / 8] |= 1 << (index % 8);
+            }
+        }
+    }
+    bytes.extend_from_slice(&walls);
+
+    let checksum = crc::checksum(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+
+    w.write_all(&bytes)
+}
+
+pub fn read<R: Read>(r: &mut R) -> Result<(LoadedMaze, Metadata), LoadError> {
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes)?;
+
+    if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(LoadError::Invalid(String::from("not a binary maze file")));
+    }
+
+    if bytes.len() < MAGIC.len() + 4 {
+        return Err(LoadError::Invalid(String::from("the file is cut short")));
+    }
+
+    // Check nothing has been damaged before trusting any of the numbers
+    let (body, tail) = bytes.split_at(bytes.len() - 4);
+    let mut reader = Reader::new(tail);
+    if reader.u32()? != crc::checksum(body) {
+        return Err(LoadError::Invalid(String::from(
+            "the checksum doesn't match, the file is damaged",
+        )));
+    }
+
+    let mut reader = Reader::new(&body[MAGIC.len()..]);
+
+    let version = reader.u16()?;
+    if version != VERSION {
+        return Err(LoadError::Invalid(format!(
+            "version {} files can't be read, only version {}",
+            version, VERSION
+        )));
+    }
+
+    let code = reader.u8()?;
+    let topology = Topology::from_code(code)
+        .ok_or_else(|| LoadError::Invalid(format!("unknown topology {}", code)))?;
+    let flags = reader.u8()?;
+
+    let width = reader.u32()?;
+    let height = reader.u32()?;
+    let start = (reader.u32()?, reader.u32()?);
+    let end = (reader.u32()?, reader.u32()?);
+    let seed = reader.u64()?;
+
+    let length = usize::from(reader.u16()?);
+    let generator = String::from_utf8(reader.take(length)?.to_vec())
+        .map_err(|_| LoadError::Invalid(String::from("the generator name isn't valid UTF-8")))?;
+
+    let pixels = u64::from(width) * u64::from(height);
+    let walls = reader.take(pixels.div_ceil(8) as usize)?;
+
+    if !reader.is_empty() {
+        return Err(LoadError::Invalid(String::from(
+            "there is more data after the walls than expected",
+        )));
+    }
+
+    let mut passages = HashSet::new();
+    for y in 0..height {
+        for x in 0..width {
+            let index = (u64::from(y) * u64::from(width) + u64::from(x)) as usize;
+            if walls[index / 8] & (1 << (index % 8)) == 0 {
+                passages.insert((x, y));
+            }
+        }
+    }
+
+    let maze = check_ends(LoadedMaze {
+        height,
+        width,
+        passages,
+        start,
+        end,
+    })?;
+
+    let metadata = Metadata {
+        topology,
+        generator,
+        seed: if flags & HAS_SEED != 0 {
+            Some(seed)
+        } else {
+            None
+        },
+    };
+
+    Ok((maze, metadata))
+}
+
+// Pulls little endian numbers off the front of a run of bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], LoadError> {
+        if self.bytes.len() < count {
+            return Err(LoadError::Invalid(String::from("the file is cut short")));
+        }
+
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, LoadError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, LoadError> {
+        let mut buffer = [0u8; 2];
+        buffer.copy_from_slice(self.take(2)?);
+        Ok(u16::from_le_bytes(buffer))
+    }
+
+    fn u32(&mut self) -> Result<u32, LoadError> {
+        let mut buffer = [0u8; 4];
+        buffer.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(buffer))
+    }
+
+    fn u64(&mut self) -> Result<u64, LoadError> {
+        let mut buffer = [0u8; 8];
+        buffer.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buffer))
+    }
+}
@@ -0,0 +1,235 @@
+extern crate serde_json;
+
+use std::collections::HashSet;
+use std::io;
+
+use mazesolver::SolveReport;
+
+use super::{check_ends, LoadError, LoadedMaze, Metadata, Topology};
+
+const VERSION: u32 = 1;
+
+// Bits of each cell's wall mask, set when the wall is there
+const NORTH: u8 = 1;
+const EAST: u8 = 2;
+const SOUTH: u8 = 4;
+const WEST: u8 = 8;
+
+/// A maze as it is written out in JSON, described by `schema/maze.schema.json`.
+///
+/// Everything is given in cells rather than pixels, so the cell in column
+/// `x` and row `y` is the maze pixel `(2x + 1, 2y + 1)`.
+#[derive(Serialize, Deserialize)]
+pub struct MazeDocument {
+    pub version: u32,
+    pub topology: Topology,
+    pub columns: u32,
+    pub rows: u32,
+    pub start: [u32; 2],
+    pub end: [u32; 2],
+    #[serde(default)]
+    pub generator: Option<String>,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Wall mask of every cell, row by row from the top left
+    pub walls: Vec<u8>,
+    #[serde(default)]
+    pub solutions: Vec<Solution>,
+}
+
+/// A path a solver found through the maze, along with the work it took.
+#[derive(Serialize, Deserialize)]
+pub struct Solution {
+    pub solver: String,
+    /// Cells along the path, from the start to the end
+    pub path: Vec<[u32; 2]>,
+    pub report: SolveReport,
+}
+
+impl MazeDocument {
+    pub fn new(maze: &LoadedMaze, metadata: &Metadata) -> io::Result<MazeDocument> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message);
+
+        if maze.width.is_multiple_of(2) || maze.height.is_multiple_of(2) {
+            return Err(invalid("only mazes with an odd size are laid out in cells"));
+        }
+
+        // Every cell has to be open and every corner post a wall, otherwise
+        // the maze can't be described by the walls around each cell
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                let open = maze.passages.contains(&(x, y));
+
+                if x % 2 == 1 && y % 2 == 1 && !open {
+                    return Err(invalid("every cell of the maze has to be open"));
+                }
+                if x % 2 == 0 && y % 2 == 0 && open {
+                    return Err(invalid("the corners between cells have to be walls"));
+                }
+            }
+        }
+
+        let start = cell(maze.start).ok_or_else(|| invalid("the start has to be a cell"))?;
+        let end = cell(maze.end).ok_or_else(|| invalid("the end has to be a cell"))?;
+
+        let columns = maze.width / 2;
+        let rows = maze.height / 2;
+
+        let mut walls = Vec::with_capacity((columns * rows) as usize);
+        for row in 0..rows {
+            for column in 0..columns {
+                let (x, y) = (2 * column + 1, 2 * row + 1);
+                let mut mask = 0;
+
+                for &(bit, position) in &[
+                    (NORTH, (x, y - 1)),
+                    (EAST, (x + 1, y)),
+                    (SOUTH, (x, y + 1)),
+                    (WEST, (x - 1, y)),
+                ] {
+                    if !maze.passages.contains(&position) {
+                        mask |= bit;
+                    }
+                }
+
+                walls.push(mask);
+            }
+        }
+
+        Ok(MazeDocument {
+            version: VERSION,
+            topology: metadata.topology,
+            columns,
+            rows,
+            start,
+            end,
+            generator: Some(metadata.generator.clone()),
+            seed: metadata.seed,
+            walls,
+            solutions: Vec::new(),
+        })
+    }
+
+    /// Adds a solver's path, given from the end back to the start like every
+    /// solver returns it.
+    pub fn add_solution(&mut self, solver: &str, path: &[&(u32, u32)], report: SolveReport) {
+        let path = path.iter().rev().filter_map(|node| cell(**node)).collect();
+
+        self.solutions.push(Solution {
+            solver: String::from(solver),
+            path,
+            report,
+        });
+    }
+
+    /// Turns the document back into a maze, checking the walls line up.
+    pub fn to_maze(&self) -> Result<(LoadedMaze, Metadata), LoadError> {
+        if self.version != VERSION {
+            return Err(LoadError::Invalid(format!(
+                "version {} documents can't be read, only version {}",
+                self.version, VERSION
+            )));
+        }
+
+        if self.columns == 0 || self.rows == 0 {
+            return Err(LoadError::Invalid(String::from("the maze has no cells")));
+        }
+
+        if self.walls.len() as u64 != u64::from(self.columns) * u64::from(self.rows) {
+            return Err(LoadError::Invalid(format!(
+                "{} wall masks given for {} by {} cells",
+                self.walls.len(),
+                self.columns,
+                self.rows
+            )));
+        }
+
+        for &(name, cell) in &[("start", self.start), ("end", self.end)] {
+            if cell[0] >= self.columns || cell[1] >= self.rows {
+                return Err(LoadError::Invalid(format!(
+                    "the {} [{}, {}] is outside the {} by {} cells",
+                    name, cell[0], cell[1], self.columns, self.rows
+                )));
+            }
+        }
+
+        let mask = |column: u32, row: u32| self.walls[(row * self.columns + column) as usize];
+        let mut passages = HashSet::new();
+
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let walls = mask(column, row);
+                let (x, y) = (2 * column + 1, 2 * row + 1);
+                passages.insert((x, y));
+
+                // Neighbouring cells have to agree on the wall between them,
+                // and the outside of the maze has to be closed off
+                let east_open = walls & EAST == 0;
+                let east_agrees = if column + 1 < self.columns {
+                    east_open == (mask(column + 1, row) & WEST == 0)
+                } else {
+                    !east_open
+                };
+
+                let south_open = walls & SOUTH == 0;
+                let south_agrees = if row + 1 < self.rows {
+                    south_open == (mask(column, row + 1) & NORTH == 0)
+                } else {
+                    !south_open
+                };
+
+                let outside_closed =
+                    (column > 0 || walls & WEST != 0) && (row > 0 || walls & NORTH != 0);
+
+                if !east_agrees || !south_agrees || !outside_closed {
+                    return Err(LoadError::Invalid(format!(
+                        "the walls of cell [{}, {}] don't line up with its neighbours",
+                        column, row
+                    )));
+                }
+
+                if east_open {
+                    passages.insert((x + 1, y));
+                }
+                if south_open {
+                    passages.insert((x, y + 1));
+                }
+            }
+        }
+
+        let pixel = |cell: [u32; 2]| (2 * cell[0] + 1, 2 * cell[1] + 1);
+
+        let maze = check_ends(LoadedMaze {
+            height: 2 * self.rows + 1,
+            width: 2 * self.columns + 1,
+            passages,
+            start: pixel(self.start),
+            end: pixel(self.end),
+        })?;
+
+        let metadata = Metadata {
+            topology: self.topology,
+            generator: self.generator.clone().unwrap_or_default(),
+            seed: self.seed,
+        };
+
+        Ok((maze, metadata))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("to_json -- unable to write the document!")
+    }
+
+    pub fn from_json(text: &str) -> Result<MazeDocument, LoadError> {
+        serde_json::from_str(text).map_err(|err| LoadError::Invalid(err.to_string()))
+    }
+}
+
+// The cell a maze pixel sits in, if it is one
+fn cell(position: (u32, u32)) -> Option<[u32; 2]> {
+    if position.0 % 2 == 1 && position.1 % 2 == 1 {
+        Some([position.0 / 2, position.1 / 2])
+    } else {
+        None
+    }
+}
@@ -0,0 +1,62 @@
+extern crate image;
+
+use mazebuilder::Mask;
+
+use super::LoadError;
+
+pub fn parse(text: &str) -> Result<Mask, LoadError> {
+    let rows: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .collect();
+
+    // Lines can be ragged, anything past the end of one is outside
+    let columns = rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0) as u32;
+    let mut mask = Mask::new(columns, rows.len() as u32);
+
+    for (row, line) in rows.iter().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+
+        for column in 0..columns {
+            let inside = chars
+                .get(column as usize)
+                .is_some_and(|&c| c != '.' && !c.is_whitespace());
+            mask.set(column, row as u32, inside);
+        }
+    }
+
+    check(mask)
+}
+
+pub fn load_image(filename: &str, threshold: u8) -> Result<Mask, LoadError> {
+    let img = image::open(filename).map_err(|err| match err {
+        image::ImageError::IoError(err) => LoadError::Io(err),
+        err => LoadError::Image(err.to_string()),
+    })?;
+
+    from_image(&img.to_luma(), threshold)
+}
+
+pub fn from_image(img: &image::GrayImage, threshold: u8) -> Result<Mask, LoadError> {
+    let mut mask = Mask::new(img.width(), img.height());
+
+    for (x, y, pixel) in img.enumerate_pixels() {
+        mask.set(x, y, pixel.data[0] < threshold);
+    }
+
+    check(mask)
+}
+
+fn check(mask: Mask) -> Result<Mask, LoadError> {
+    if mask.count() == 0 {
+        return Err(LoadError::Invalid(String::from(
+            "the mask doesn't have any cells inside it",
+        )));
+    }
+
+    Ok(mask)
+}
@@ -0,0 +1,609 @@
+mod ascii;
+mod binary;
+mod json;
+mod mask;
+mod png;
+
+pub use self::json::{MazeDocument, Solution};
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use mazebuilder::Mask;
+
+/// A maze read in from a file, along with where to start and finish.
+pub struct LoadedMaze {
+    pub height: u32,
+    pub width: u32,
+    pub passages: HashSet<(u32, u32)>,
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+}
+
+/// The shape of grid a maze is laid out on.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Topology {
+    /// Square cells on the odd coordinates with walls between them
+    Grid,
+}
+
+impl Topology {
+    fn code(self) -> u8 {
+        match self {
+            Topology::Grid => 0,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Topology> {
+        match code {
+            0 => Some(Topology::Grid),
+            _ => None,
+        }
+    }
+}
+
+/// How a maze was made, kept alongside it in binary maze files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metadata {
+    pub topology: Topology,
+    /// Name of the generator, along with any options it was run with
+    pub generator: String,
+    /// Seed the generator's random numbers came from, if it was given one
+    pub seed: Option<u64>,
+}
+
+/// Reasons a maze file could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file isn't an image that can be decoded
+    Image(String),
+    /// The file was read but doesn't describe a usable maze
+    Invalid(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Io(ref err) => write!(f, "unable to read the maze: {}", err),
+            LoadError::Image(ref message) => write!(f, "unable to decode the image: {}", message),
+            LoadError::Invalid(ref message) => write!(f, "not a usable maze: {}", message),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> LoadError {
+        LoadError::Io(err)
+    }
+}
+
+/// Loads a black and white maze image, such as the ones this tool saves.
+///
+/// Pixels at least as bright as the threshold are passages. A green pixel
+/// marks the start and a red one the end, otherwise they are taken to be in
+/// the top left and bottom right corners. Images drawn with more than one
+/// pixel per passage are scaled back down.
+pub fn load_png(filename: &str, threshold: u8) -> Result<LoadedMaze, LoadError> {
+    png::load(filename, threshold)
+}
+
+/// Reads a maze drawn as text, with `#` for walls and `.` or a space for
+/// passages. The start and end can be marked with `S` and `E`, otherwise they
+/// are taken to be in the top left and bottom right corners.
+pub fn parse_ascii(text: &str) -> Result<LoadedMaze, LoadError> {
+    ascii::parse(text)
+}
+
+/// Writes a maze out as text that `parse_ascii` can read back in.
+pub fn to_ascii(maze: &LoadedMaze) -> String {
+    ascii::serialize(maze)
+}
+
+pub fn load_ascii(filename: &str) -> Result<LoadedMaze, LoadError> {
+    parse_ascii(&fs::read_to_string(filename)?)
+}
+
+pub fn save_ascii(maze: &LoadedMaze, filename: &str) -> io::Result<()> {
+    fs::write(filename, to_ascii(maze))
+}
+
+/// Writes a maze in the compact binary format, along with how it was made.
+pub fn write_binary<W: Write>(w: &mut W, maze: &LoadedMaze, metadata: &Metadata) -> io::Result<()> {
+    binary::write(w, maze, metadata)
+}
+
+/// Reads a maze written by `write_binary`, checking it hasn't been damaged.
+pub fn read_binary<R: Read>(r: &mut R) -> Result<(LoadedMaze, Metadata), LoadError> {
+    binary::read(r)
+}
+
+pub fn save_binary(maze: &LoadedMaze, metadata: &Metadata, filename: &str) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    write_binary(&mut writer, maze, metadata)?;
+    writer.flush()
+}
+
+pub fn load_binary(filename: &str) -> Result<(LoadedMaze, Metadata), LoadError> {
+    read_binary(&mut BufReader::new(File::open(filename)?))
+}
+
+pub fn save_json(document: &MazeDocument, filename: &str) -> io::Result<()> {
+    fs::write(filename, document.to_json())
+}
+
+/// Reads a maze written as JSON, along with any solutions saved with it.
+pub fn load_json(filename: &str) -> Result<MazeDocument, LoadError> {
+    MazeDocument::from_json(&fs::read_to_string(filename)?)
+}
+
+/// Reads the shape of a maze from text, with one character for each cell.
+/// Spaces and `.` are outside the maze and any other character is inside it.
+pub fn parse_mask(text: &str) -> Result<Mask, LoadError> {
+    mask::parse(text)
+}
+
+pub fn load_mask_text(filename: &str) -> Result<Mask, LoadError> {
+    parse_mask(&fs::read_to_string(filename)?)
+}
+
+/// Reads the shape of a maze from an image, with one pixel for each cell.
+/// Pixels darker than the threshold are inside the maze, so a black shape on
+/// a white background gives a maze in that shape.
+pub fn load_mask_image(filename: &str, threshold: u8) -> Result<Mask, LoadError> {
+    mask::load_image(filename, threshold)
+}
+
+// Checks that the start and end can actually be stood on, and that the
+// passages are walled in all round
+fn check_ends(loaded: LoadedMaze) -> Result<LoadedMaze, LoadError> {
+    if !loaded.passages.contains(&loaded.start) {
+        return Err(LoadError::Invalid(format!(
+            "the start {:?} is not a passage",
+            loaded.start
+        )));
+    }
+
+    if !loaded.passages.contains(&loaded.end) {
+        return Err(LoadError::Invalid(format!(
+            "the end {:?} is not a passage",
+            loaded.end
+        )));
+    }
+
+    // Solvers step to either side of every passage, so none may sit on
+    // the outside edge
+    let on_edge = loaded
+        .passages
+        .iter()
+        .find(|&&(x, y)| x == 0 || y == 0 || x + 1 >= loaded.width || y + 1 >= loaded.height);
+
+    if let Some(passage) = on_edge {
+        return Err(LoadError::Invalid(format!(
+            "the passage at {:?} is on the edge of the maze",
+            passage
+        )));
+    }
+
+    Ok(loaded)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate image;
+
+    use super::*;
+    use mazebuilder::{generate_maze, Generator};
+    use mazesolver::{solve_maze, Solver};
+
+    fn maze_image(maze: &HashSet<(u32, u32)>, size: u32, scale: u32) -> image::RgbImage {
+        image::ImageBuffer::from_fn(size * scale, size * scale, |x, y| {
+            if maze.contains(&(x / scale, y / scale)) {
+                image::Rgb([255u8, 255, 255])
+            } else {
+                image::Rgb([0u8, 0, 0])
+            }
+        })
+    }
+
+    #[test]
+    fn test_load_scaled_image() {
+        let maze = generate_maze(
+            Generator::DFS {
+                height: 21,
+                width: 21,
+            },
+            false,
+        );
+        let img = maze_image(&maze, 21, 3);
+
+        let loaded = png::from_image(&img, 128).unwrap();
+        assert_eq!((loaded.height, loaded.width), (21, 21));
+        assert_eq!(loaded.passages, maze);
+        assert_eq!(loaded.start, (1, 1));
+        assert_eq!(loaded.end, (19, 19));
+    }
+
+    #[test]
+    fn test_coloured_start_and_end() {
+        let maze = generate_maze(
+            Generator::Kruskal {
+                height: 11,
+                width: 11,
+            },
+            false,
+        );
+        let mut img = maze_image(&maze, 11, 1);
+
+        // Every cell is a passage, so swap the corners over
+        img.put_pixel(9, 9, image::Rgb([0u8, 200, 0]));
+        img.put_pixel(1, 1, image::Rgb([220u8, 30, 30]));
+
+        let loaded = png::from_image(&img, 128).unwrap();
+        assert_eq!(loaded.passages, maze);
+        assert_eq!(loaded.start, (9, 9));
+        assert_eq!(loaded.end, (1, 1));
+    }
+
+    #[test]
+    fn test_reject_blocked_start() {
+        let img = image::ImageBuffer::from_pixel(5, 5, image::Rgb([0u8, 0, 0]));
+
+        match png::from_image(&img, 128) {
+            Err(LoadError::Invalid(_)) => {}
+            _ => panic!("a maze with no passages should not load"),
+        }
+    }
+
+    #[test]
+    fn test_ascii_round_trip() {
+        let maze = generate_maze(
+            Generator::Kruskal {
+                height: 15,
+                width: 21,
+            },
+            true,
+        );
+        let loaded = LoadedMaze {
+            height: 15,
+            width: 21,
+            passages: maze,
+            start: (1, 1),
+            end: (19, 13),
+        };
+
+        let text = to_ascii(&loaded);
+        assert!(text.starts_with("#####"));
+        assert_eq!(&text[22..24], "#S");
+
+        let parsed = parse_ascii(&text).unwrap();
+        assert_eq!((parsed.height, parsed.width), (15, 21));
+        assert_eq!(parsed.passages, loaded.passages);
+        assert_eq!((parsed.start, parsed.end), ((1, 1), (19, 13)));
+        assert_eq!(to_ascii(&parsed), text);
+    }
+
+    #[test]
+    fn test_ascii_spaces_and_markers() {
+        let parsed = parse_ascii("#####\r\n#  E#\r\n# ###\r\n#S  #\r\n#####\r\n").unwrap();
+
+        assert_eq!(parsed.passages.len(), 7);
+        assert_eq!(parsed.start, (1, 3));
+        assert_eq!(parsed.end, (3, 1));
+    }
+
+    #[test]
+    fn test_ascii_errors() {
+        let errors = [
+            // Ragged, unknown character, two starts, end in a wall,
+            "#####\n#S.E#\n####\n",
+            "#####\n#S?E#\n#####\n",
+            "#####\n#SSE#\n#####\n",
+            "#####\n#..##\n#####\n",
+            // A passage open to the outside
+            "#.###\n#S  #\n### #\n#  E#\n#####",
+        ];
+
+        for text in &errors {
+            match parse_ascii(text) {
+                Err(LoadError::Invalid(_)) => {}
+                _ => panic!("{:?} should not parse", text),
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixtures_with_every_solver() {
+        let fixtures = [
+            include_str!("../../fixtures/perfect.txt"),
+            include_str!("../../fixtures/loops.txt"),
+        ];
+
+        for fixture in &fixtures {
+            let maze = parse_ascii(fixture).unwrap();
+
+            for solver in &Solver::ALL {
+                let (path, _) = solve_maze(*solver, maze.start, maze.end, &maze.passages, false);
+                let path = path.expect("every solver should find a way through the fixtures");
+
+                assert_eq!(*path[0], maze.end);
+                assert_eq!(**path.last().unwrap(), maze.start);
+            }
+        }
+    }
+
+    fn binary_fixture() -> (LoadedMaze, Metadata) {
+        let maze = generate_maze(
+            Generator::DFS {
+                height: 13,
+                width: 17,
+            },
+            true,
+        );
+        let loaded = LoadedMaze {
+            height: 13,
+            width: 17,
+            passages: maze,
+            start: (1, 1),
+            end: (15, 11),
+        };
+        let metadata = Metadata {
+            topology: Topology::Grid,
+            generator: String::from("dfs+imperfect"),
+            seed: Some(42),
+        };
+
+        (loaded, metadata)
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let (loaded, metadata) = binary_fixture();
+
+        let mut bytes = Vec::new();
+        write_binary(&mut bytes, &loaded, &metadata).unwrap();
+
+        // Header, generator name, a bit per pixel and the checksum
+        assert_eq!(&bytes[..4], b"MAZE");
+        assert_eq!(bytes.len(), 40 + 2 + 13 + (13 * 17usize).div_ceil(8) + 4);
+
+        let (read, read_metadata) = read_binary(&mut &bytes[..]).unwrap();
+        assert_eq!((read.height, read.width), (13, 17));
+        assert_eq!(read.passages, loaded.passages);
+        assert_eq!((read.start, read.end), (loaded.start, loaded.end));
+        assert_eq!(read_metadata, metadata);
+
+        // Writing it back out again gives the same bytes
+        let mut again = Vec::new();
+        write_binary(&mut again, &read, &read_metadata).unwrap();
+        assert_eq!(again, bytes);
+    }
+
+    #[test]
+    fn test_binary_without_seed() {
+        let (loaded, mut metadata) = binary_fixture();
+        metadata.seed = None;
+
+        let mut bytes = Vec::new();
+        write_binary(&mut bytes, &loaded, &metadata).unwrap();
+
+        let (_, read_metadata) = read_binary(&mut &bytes[..]).unwrap();
+        assert_eq!(read_metadata.seed, None);
+    }
+
+    #[test]
+    fn test_binary_damage_is_caught() {
+        let (loaded, metadata) = binary_fixture();
+
+        let mut bytes = Vec::new();
+        write_binary(&mut bytes, &loaded, &metadata).unwrap();
+
+        let mut flipped = bytes.clone();
+        flipped[60] ^= 0x10;
+
+        let mut newer = bytes.clone();
+        newer[4] = 2;
+
+        let damaged = [
+            flipped,
+            newer,
+            bytes[..bytes.len() - 1].to_vec(),
+            b"GIF89a".to_vec(),
+        ];
+
+        for bytes in &damaged {
+            match read_binary(&mut &bytes[..]) {
+                Err(LoadError::Invalid(_)) => {}
+                _ => panic!("damaged files should not load"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip_with_every_generator() {
+        for &imperfect in &[false, true] {
+            let generators = vec![
+                Generator::DFS {
+                    height: 15,
+                    width: 21,
+                },
+                Generator::Kruskal {
+                    height: 15,
+                    width: 21,
+                },
+            ];
+
+            for generator in generators {
+                let name = generator.name();
+                let loaded = LoadedMaze {
+                    height: 15,
+                    width: 21,
+                    passages: generate_maze(generator, imperfect),
+                    start: (1, 1),
+                    end: (19, 13),
+                };
+                let metadata = Metadata {
+                    topology: Topology::Grid,
+                    generator: String::from(name),
+                    seed: None,
+                };
+
+                let mut document = MazeDocument::new(&loaded, &metadata).unwrap();
+                for solver in &Solver::ALL {
+                    let (path, report) =
+                        solve_maze(*solver, loaded.start, loaded.end, &loaded.passages, false);
+                    document.add_solution(solver.name(), &path.unwrap(), report);
+                }
+
+                let text = document.to_json();
+                let read = MazeDocument::from_json(&text).unwrap();
+                let (maze, read_metadata) = read.to_maze().unwrap();
+
+                assert_eq!((maze.height, maze.width), (15, 21));
+                assert_eq!(maze.passages, loaded.passages);
+                assert_eq!((maze.start, maze.end), (loaded.start, loaded.end));
+                assert_eq!(read_metadata, metadata);
+
+                assert_eq!(read.solutions.len(), Solver::ALL.len());
+                for solution in &read.solutions {
+                    assert_eq!(solution.path[0], [0, 0]);
+                    assert_eq!(*solution.path.last().unwrap(), [9, 6]);
+                }
+
+                // Writing it back out again gives the same text
+                assert_eq!(read.to_json(), text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_layout() {
+        // A single corridor running east from the start to the end
+        let loaded = parse_ascii("#######\n#S...E#\n#######\n").unwrap();
+        let metadata = Metadata {
+            topology: Topology::Grid,
+            generator: String::from("by hand"),
+            seed: Some(7),
+        };
+
+        let document = MazeDocument::new(&loaded, &metadata).unwrap();
+        assert_eq!((document.columns, document.rows), (3, 1));
+        assert_eq!((document.start, document.end), ([0, 0], [2, 0]));
+        assert_eq!(document.walls, vec![1 | 4 | 8, 1 | 4, 1 | 2 | 4]);
+
+        let text = document.to_json();
+        assert!(text.contains("\"topology\": \"grid\""));
+        assert!(text.contains("\"seed\": 7"));
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let valid = r#"{"version": 1, "topology": "grid", "columns": 2, "rows": 1,
+            "start": [0, 0], "end": [1, 0], "walls": [13, 7]}"#;
+        assert!(MazeDocument::from_json(valid).unwrap().to_maze().is_ok());
+
+        let invalid = [
+            // Newer version, missing wall, cells disagreeing, open outside,
+            // end off the maze, start too far out to turn into a pixel
+            valid.replace("\"version\": 1", "\"version\": 2"),
+            valid.replace("[13, 7]", "[13]"),
+            valid.replace("[13, 7]", "[15, 7]"),
+            valid.replace("[13, 7]", "[12, 7]"),
+            valid.replace("[1, 0]", "[2, 0]"),
+            valid.replace("[0, 0]", "[4294967295, 0]"),
+        ];
+
+        for text in &invalid {
+            let result = MazeDocument::from_json(text).and_then(|document| document.to_maze());
+            match result {
+                Err(LoadError::Invalid(_)) => {}
+                _ => panic!("{} should not load", text),
+            }
+        }
+
+        match MazeDocument::from_json("{\"version\": 1}") {
+            Err(LoadError::Invalid(_)) => {}
+            _ => panic!("documents missing fields should not load"),
+        }
+    }
+
+    #[test]
+    fn test_unknown_topology() {
+        let (loaded, metadata) = binary_fixture();
+
+        // Files for grids the formats can't hold are turned away
+        let mut bytes = Vec::new();
+        write_binary(&mut bytes, &loaded, &metadata).unwrap();
+        bytes[6] = 1;
+        let length = bytes.len() - 4;
+        let checksum = ::crc::checksum(&bytes[..length]);
+        bytes[length..].copy_from_slice(&checksum.to_le_bytes());
+        match read_binary(&mut &bytes[..]) {
+            Err(LoadError::Invalid(message)) => assert!(message.contains("topology")),
+            _ => panic!("unknown topologies should not load"),
+        }
+
+        let text = MazeDocument::new(&loaded, &metadata).unwrap().to_json();
+        let text = text.replace("\"grid\"", "\"hex\"");
+        match MazeDocument::from_json(&text) {
+            Err(LoadError::Invalid(_)) => {}
+            _ => panic!("unknown topologies should not load"),
+        }
+    }
+
+    #[test]
+    fn test_json_needs_cells() {
+        // The start sits on the wall between two cells
+        let loaded = parse_ascii("#####\n#.S.#\n#####\n").unwrap();
+        let metadata = Metadata {
+            topology: Topology::Grid,
+            generator: String::new(),
+            seed: None,
+        };
+
+        assert!(MazeDocument::new(&loaded, &metadata).is_err());
+    }
+
+    #[test]
+    fn test_text_mask() {
+        let mask = parse_mask("..##\n.###x\n#\n").unwrap();
+
+        assert_eq!((mask.columns(), mask.rows()), (5, 3));
+        assert_eq!(mask.size(), (7, 11));
+        assert_eq!(mask.count(), 7);
+        assert!(!mask.is_inside(0, 0));
+        assert!(mask.is_inside(4, 1));
+        assert!(!mask.is_inside(1, 2));
+        assert_eq!(mask.nodes()[0], (5, 1));
+
+        match parse_mask(" . \n\n") {
+            Err(LoadError::Invalid(_)) => {}
+            _ => panic!("an empty mask should not load"),
+        }
+    }
+
+    #[test]
+    fn test_image_mask() {
+        // A dark ring on a light background
+        let img = image::ImageBuffer::from_fn(5, 5, |x, y| {
+            if x == 0 || y == 0 || x == 4 || y == 4 || (x, y) == (2, 2) {
+                image::Luma([250u8])
+            } else {
+                image::Luma([10u8])
+            }
+        });
+
+        let mask = mask::from_image(&img, 128).unwrap();
+        assert_eq!(mask.count(), 8);
+        assert!(mask.contains((3, 3)));
+        assert!(!mask.contains((5, 5)));
+        assert!(!mask.contains((4, 3)));
+    }
+}
@@ -0,0 +1,139 @@
+extern crate image;
+
+use std::collections::HashSet;
+
+use super::{check_ends, LoadError, LoadedMaze};
+
+// What a pixel of the image stands for
+#[derive(Clone, Copy, PartialEq)]
+enum Pixel {
+    Wall,
+    Passage,
+    Start,
+    End,
+}
+
+pub fn load(filename: &str, threshold: u8) -> Result<LoadedMaze, LoadError> {
+    let img = image::open(filename).map_err(|err| match err {
+        image::ImageError::IoError(err) => LoadError::Io(err),
+        err => LoadError::Image(err.to_string()),
+    })?;
+
+    from_image(&img.to_rgb(), threshold)
+}
+
+pub fn from_image(img: &image::RgbImage, threshold: u8) -> Result<LoadedMaze, LoadError> {
+    let classify = |x: u32, y: u32| classify(img.get_pixel(x, y), threshold);
+
+    // Each passage or wall covers a square of scale pixels
+    let scale = block_size(img.width(), img.height(), &classify);
+    let width = img.width() / scale;
+    let height = img.height() / scale;
+
+    if width < 3 || height < 3 {
+        return Err(LoadError::Invalid(format!(
+            "a {} by {} maze is too small to have a passage",
+            width, height
+        )));
+    }
+
+    let mut passages = HashSet::new();
+    let mut start = None;
+    let mut end = None;
+
+    // Read each block from its middle pixel, clear of any blurring at the edges
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = classify(x * scale + scale / 2, y * scale + scale / 2);
+
+            match pixel {
+                Pixel::Wall => continue,
+                Pixel::Start => {
+                    start.get_or_insert((x, y));
+                }
+                Pixel::End => {
+                    end.get_or_insert((x, y));
+                }
+                Pixel::Passage => {}
+            }
+
+            passages.insert((x, y));
+        }
+    }
+
+    check_ends(LoadedMaze {
+        height,
+        width,
+        passages,
+        start: start.unwrap_or((1, 1)),
+        end: end.unwrap_or((width - 2, height - 2)),
+    })
+}
+
+fn classify(pixel: &image::Rgb<u8>, threshold: u8) -> Pixel {
+    let [r, g, b] = pixel.data;
+    let brightest = r.max(g).max(b);
+    let dimmest = r.min(g).min(b);
+
+    // Strongly coloured pixels mark the start in green and the end in red
+    if brightest - dimmest >= 96 {
+        if g == brightest {
+            return Pixel::Start;
+        } else if r == brightest {
+            return Pixel::End;
+        }
+    }
+
+    let brightness = (u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114) / 1000;
+
+    if brightness >= u32::from(threshold) {
+        Pixel::Passage
+    } else {
+        Pixel::Wall
+    }
+}
+
+// The largest block size that every run of matching pixels, across and
+// down, is a whole number of
+fn block_size<F>(width: u32, height: u32, classify: &F) -> u32
+where
+    F: Fn(u32, u32) -> Pixel,
+{
+    let mut size = 0;
+
+    for y in 0..height {
+        let mut run = 1;
+        for x in 1..width {
+            if classify(x, y) == classify(x - 1, y) {
+                run += 1;
+            } else {
+                size = gcd(size, run);
+                run = 1;
+            }
+        }
+        size = gcd(size, run);
+    }
+
+    for x in 0..width {
+        let mut run = 1;
+        for y in 1..height {
+            if classify(x, y) == classify(x, y - 1) {
+                run += 1;
+            } else {
+                size = gcd(size, run);
+                run = 1;
+            }
+        }
+        size = gcd(size, run);
+    }
+
+    size.max(1)
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
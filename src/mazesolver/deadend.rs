@@ -0,0 +1,70 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use grids::{Grid, GridMaze};
+
+use super::{walk_back, SolveReport};
+
+// Gives whether each cell was filled in as well as the path, so the filled
+// cells can be greyed out
+pub fn dead_end_filling<G: Grid>(
+    maze: &GridMaze<G>,
+    start: usize,
+    end: usize,
+    report: &mut SolveReport<usize>,
+) -> (Option<Vec<usize>>, Vec<bool>) {
+    let mut filled = vec![false; maze.grid.cell_count()];
+    let open_passages = |filled: &[bool], cell: usize| {
+        maze.passages(cell)
+            .into_iter()
+            .filter(|&next| !filled[next])
+            .count()
+    };
+
+    let mut queue: VecDeque<usize> = (0..filled.len())
+        .filter(|&cell| maze.passages(cell).len() == 1)
+        .collect();
+
+    // Fill each dead end, which may turn the cell it led out of into a dead
+    // end itself, so check that one next. The start and end are never filled.
+    while let Some(cell) = queue.pop_front() {
+        if cell == start || cell == end || filled[cell] || open_passages(&filled, cell) != 1 {
+            continue;
+        }
+
+        filled[cell] = true;
+        report.expand(&cell);
+
+        for next in maze.passages(cell) {
+            if !filled[next] {
+                queue.push_back(next);
+            }
+        }
+        report.frontier(queue.len());
+    }
+
+    // Whatever is left open leads from the start to the end
+    let mut came_from: HashMap<usize, usize> = HashMap::new();
+    let mut queue = VecDeque::new();
+    came_from.insert(start, start);
+    queue.push_back(start);
+
+    while let Some(current) = queue.pop_front() {
+        if current == end {
+            return (Some(walk_back(&came_from, end)), filled);
+        }
+
+        for next in maze.passages(current) {
+            if filled[next] {
+                continue;
+            }
+
+            if let Entry::Vacant(entry) = came_from.entry(next) {
+                entry.insert(current);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    (None, filled)
+}
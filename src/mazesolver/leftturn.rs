@@ -1,156 +1,85 @@
 use std::collections::HashSet;
+use std::f64::consts::PI;
 
-// location tracking object
-struct Person {
-    x: u32,
-    y: u32,
-    facing: Direction,
-}
-
-#[derive(Clone, Copy)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-// Entry function
-pub fn left_first(
-    start: (u32, u32),
-    end: (u32, u32),
-    maze: &HashSet<(u32, u32)>,
-) -> Option<Vec<&(u32, u32)>> {
-    // Create a person at the start of the maze
-    let mut person = Person {
-        x: start.0,
-        y: start.1,
-        facing: Direction::South,
-    };
-
-    let at_end = true;
-    let mut path = Vec::new();
-
-    // While the person is not at the end of the maze
-    while at_end {
-        // Add the current node to the path.
-        path.push(
-            maze.get(&(person.x, person.y))
-                .expect("Tried to get maze position using player, but no cell found!"),
-        );
-
-        // Check if we are at the end of the maze
-        if (person.x, person.y) == end {
-            return Some(reverse_path(path));
-        }
+use grids::{Grid, GridMaze, Stair};
 
-        // Find the next position to move to.
-        let (next_step, new_facing) = find_next_step(&maze, &person);
+use super::SolveReport;
 
-        // Move the person to that position
-        person = move_person(person, next_step, new_facing);
-    }
+// The heading wall followers start out facing, straight down the drawing
+const PREFERRED: f64 = PI / 2.0;
 
-    None
-}
+// Headings closer than this are treated as the same direction
+const EPSILON: f64 = 1e-6;
 
-fn get_direction(
-    maze: &HashSet<(u32, u32)>,
-    person: &Person,
-    direction: Direction,
-) -> Option<(u32, u32)> {
-    match direction {
-        Direction::North => (get_node(maze, person.x, person.y - 1)),
-        Direction::East => (get_node(maze, person.x + 1, person.y)),
-        Direction::South => (get_node(maze, person.x, person.y + 1)),
-        Direction::West => (get_node(maze, person.x - 1, person.y)),
-    }
-}
+// Wall followers need to know which way is left, so they go by the direction
+// from the middle of one cell to the next on the drawing
+pub fn left_turn<G: Grid>(
+    maze: &GridMaze<G>,
+    start: usize,
+    end: usize,
+    report: &mut SolveReport<usize>,
+) -> Option<Vec<usize>> {
+    let mut path = vec![start];
+    let mut current = start;
+    let mut facing = PREFERRED;
 
-fn get_node(maze: &HashSet<(u32, u32)>, x: u32, y: u32) -> Option<(u32, u32)> {
-    let node = maze.get(&(x, y));
+    // Arriving somewhere the same way twice means the walk goes round forever
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
 
-    match node {
-        Some(value) => Some(*value),
-        None => None,
-    }
-}
+    loop {
+        report.expand(&current);
+        report.frontier(1);
 
-fn move_person(_person: Person, position: (u32, u32), new_facing: Direction) -> Person {
-    Person {
-        x: position.0,
-        y: position.1,
-        facing: new_facing,
-    }
-}
+        if current == end {
+            return Some(path);
+        }
 
-fn look_left(maze: &HashSet<(u32, u32)>, person: &Person) -> Option<((u32, u32), Direction)> {
-    match person.facing {
-        Direction::North => get_cell_from_direction(maze, person, Direction::West),
-        Direction::East => get_cell_from_direction(maze, person, Direction::North),
-        Direction::South => get_cell_from_direction(maze, person, Direction::East),
-        Direction::West => get_cell_from_direction(maze, person, Direction::South),
-    }
-}
+        let (next, heading) = leftmost(maze, current, facing)?;
+        if !seen.insert((current, next)) {
+            return None;
+        }
 
-fn look_right(maze: &HashSet<(u32, u32)>, person: &Person) -> Option<((u32, u32), Direction)> {
-    match person.facing {
-        Direction::North => get_cell_from_direction(maze, person, Direction::East),
-        Direction::East => get_cell_from_direction(maze, person, Direction::South),
-        Direction::South => get_cell_from_direction(maze, person, Direction::West),
-        Direction::West => get_cell_from_direction(maze, person, Direction::North),
+        path.push(next);
+        current = next;
+        facing = heading;
     }
 }
 
-fn look_back(maze: &HashSet<(u32, u32)>, person: &Person) -> Option<((u32, u32), Direction)> {
-    match person.facing {
-        Direction::North => get_cell_from_direction(maze, person, Direction::South),
-        Direction::East => get_cell_from_direction(maze, person, Direction::West),
-        Direction::South => get_cell_from_direction(maze, person, Direction::North),
-        Direction::West => get_cell_from_direction(maze, person, Direction::East),
+// Direction from the middle of one cell to the middle of the next. Stairs
+// lead off to another part of the drawing, so they get diagonals of their
+// own instead, opposite each other and clear of the sides of a square.
+fn heading<G: Grid>(grid: &G, from: usize, to: usize) -> f64 {
+    match grid.stairs(from).into_iter().find(|&(next, _)| next == to) {
+        Some((_, Stair::Up)) => return -PI / 4.0,
+        Some((_, Stair::Down)) => return 3.0 * PI / 4.0,
+        None => {}
     }
-}
 
-fn get_cell_from_direction(
-    maze: &HashSet<(u32, u32)>,
-    person: &Person,
-    dir: Direction,
-) -> Option<((u32, u32), Direction)> {
-    match get_direction(&maze, &person, dir) {
-        Some(position) => Some((position, dir)),
-        None => None,
-    }
+    let (a, b) = (grid.centre(from), grid.centre(to));
+    (b.1 - a.1).atan2(b.0 - a.0)
 }
 
-fn find_next_step(maze: &HashSet<(u32, u32)>, person: &Person) -> ((u32, u32), Direction) {
-    // Look left, if there is a path then that is the way to move,
-    // if not look foward and move if there is a path.
-    // if there is nothing left or foward, try right.
-    // finaly if there is nothing else, go back.
-    if let Some(left) = look_left(maze, person) {
-        return left;
-    };
-
-    if let Some(forward) = get_direction(maze, person, person.facing) {
-        return (forward, person.facing);
-    };
-
-    if let Some(right) = look_right(maze, person) {
-        return right;
-    };
-
-    look_back(maze, person).expect("find_next_step -- No Path back from current location found!")
+// The open passage whose heading is the first one found turning clockwise
+// from `from`, counting `from` itself last, along with how far round it is
+fn first_clockwise<G: Grid>(
+    maze: &GridMaze<G>,
+    cell: usize,
+    from: f64,
+) -> Option<(usize, f64, f64)> {
+    maze.passages(cell)
+        .into_iter()
+        .map(|next| {
+            let heading = heading(&maze.grid, cell, next);
+            let mut angle = (heading - from).rem_euclid(2.0 * PI);
+            if angle < EPSILON {
+                angle += 2.0 * PI;
+            }
+            (next, heading, angle)
+        })
+        .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
 }
 
-fn reverse_path<'a>(mut path: Vec<&'a (u32, u32)>) -> Vec<&'a (u32, u32)> {
-    let mut reversed_path: Vec<&'a (u32, u32)> = Vec::new();
-
-    while path.len() > 0 {
-        reversed_path.push(
-            path.pop()
-                .expect("reverse_path -- failed to reverse the path"),
-        );
-    }
-
-    reversed_path
+// Keeping a hand on the left wall, the first way on clockwise from behind
+fn leftmost<G: Grid>(maze: &GridMaze<G>, cell: usize, facing: f64) -> Option<(usize, f64)> {
+    first_clockwise(maze, cell, facing + PI).map(|(next, heading, _)| (next, heading))
 }
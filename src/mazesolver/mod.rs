@@ -1,26 +1,542 @@
+mod a_star;
 mod bfs;
+mod deadend;
 mod leftturn;
-mod a_star;
+mod pledge;
+mod report;
+mod tremaux;
+
+use std::collections::{HashMap, HashSet};
+
+use grids::{Grid, GridMaze, SquareGrid};
 
-use std::collections::HashSet;
+pub use self::report::SolveReport;
 
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy)]
 pub enum Solver {
     BFS,
     LeftTurn,
     AStar,
+    Pledge,
+    Tremaux,
+    DeadEndFilling,
+}
+
+impl Solver {
+    /// Every solver, in the order they are offered.
+    pub const ALL: [Solver; 6] = [
+        Solver::BFS,
+        Solver::LeftTurn,
+        Solver::AStar,
+        Solver::Pledge,
+        Solver::Tremaux,
+        Solver::DeadEndFilling,
+    ];
+
+    /// Short name for the solver, used in file names and saved solutions.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Solver::BFS => "bfs",
+            Solver::LeftTurn => "left-turn",
+            Solver::AStar => "a-star",
+            Solver::Pledge => "pledge",
+            Solver::Tremaux => "tremaux",
+            Solver::DeadEndFilling => "dead-end",
+        }
+    }
+
+    /// Whether the solver walks through the maze a step at a time, coming
+    /// back over old ground, rather than searching outwards.
+    pub fn walks(&self) -> bool {
+        match *self {
+            Solver::LeftTurn | Solver::Pledge | Solver::Tremaux => true,
+            Solver::BFS | Solver::AStar | Solver::DeadEndFilling => false,
+        }
+    }
 }
 
-pub fn solve_maze<'a>(
+/// Solves the maze with the chosen solver, returning the path along with a
+/// report of the work done. Pass `trace` to keep every expansion in order.
+///
+/// Paths are given from the end back to the start.
+pub fn solve_maze(
     solver: Solver,
     start: (u32, u32),
     end: (u32, u32),
-    maze: &'a HashSet<(u32, u32)>,
-) -> Option<Vec<&'a (u32, u32)>> {
+    maze: &HashSet<(u32, u32)>,
+    trace: bool,
+) -> (Option<Vec<&(u32, u32)>>, SolveReport) {
+    let pixels = on_grid(maze);
+
+    let (path, report) = match ends(&pixels, start, end, maze) {
+        Some((start, end)) => solve_grid(solver, &pixels, start, end, trace),
+        None => (None, SolveReport::new(trace)),
+    };
+
+    (to_nodes(&pixels, path, maze), to_pixels(&pixels, report))
+}
+
+/// Solves a maze on any grid with the chosen solver, returning the cells of
+/// the path from the start to the end along with a report of the work done.
+pub fn solve_grid<G: Grid>(
+    solver: Solver,
+    maze: &GridMaze<G>,
+    start: usize,
+    end: usize,
+    trace: bool,
+) -> (Option<Vec<usize>>, SolveReport<usize>) {
+    let mut report = SolveReport::new(trace);
+
     let path = match solver {
-        Solver::BFS => bfs::breadth_first_search(start, end, maze),
-        Solver::LeftTurn => leftturn::left_first(start, end, maze),
-        Solver::AStar => a_star::solve(start, end, maze),
+        Solver::BFS => bfs::breadth_first_search(maze, start, end, &mut report),
+        Solver::LeftTurn => leftturn::left_turn(maze, start, end, &mut report),
+        Solver::AStar => a_star::solve(maze, start, end, &mut report),
+        Solver::Pledge => pledge::pledge(maze, start, end, &mut report),
+        Solver::Tremaux => tremaux::tremaux(maze, start, end, &mut report).0,
+        Solver::DeadEndFilling => deadend::dead_end_filling(maze, start, end, &mut report).0,
+    };
+
+    report.finish(&path);
+    (path, report)
+}
+
+/// How many times the passages into each node were marked.
+pub type Marks<'a> = HashMap<&'a (u32, u32), u8>;
+
+/// Nodes that were filled in as dead ends.
+pub type Filled<'a> = HashSet<&'a (u32, u32)>;
+
+/// Solves the maze with Trémaux's algorithm, also returning how many times
+/// each node was marked on the way.
+pub fn solve_tremaux(
+    start: (u32, u32),
+    end: (u32, u32),
+    maze: &HashSet<(u32, u32)>,
+    trace: bool,
+) -> (Option<Vec<&(u32, u32)>>, Marks<'_>, SolveReport) {
+    let pixels = on_grid(maze);
+    let mut report = SolveReport::new(trace);
+
+    let (path, passages) = match ends(&pixels, start, end, maze) {
+        Some((start, end)) => tremaux::tremaux(&pixels, start, end, &mut report),
+        None => (None, HashMap::new()),
+    };
+    report.finish(&path);
+
+    // A node takes the highest mark of any passage leading into it
+    let mut marks = HashMap::new();
+    for (&(a, b), &count) in &passages {
+        for &cell in &[a, b] {
+            let mark = marks.entry(node(&pixels, maze, cell)).or_insert(0);
+            if count > *mark {
+                *mark = count;
+            }
+        }
+    }
+
+    (
+        to_nodes(&pixels, path, maze),
+        marks,
+        to_pixels(&pixels, report),
+    )
+}
+
+/// Solves the maze by filling in dead ends, also returning the nodes that
+/// were filled so they can be greyed out.
+pub fn solve_dead_end_filling(
+    start: (u32, u32),
+    end: (u32, u32),
+    maze: &HashSet<(u32, u32)>,
+    trace: bool,
+) -> (Option<Vec<&(u32, u32)>>, Filled<'_>, SolveReport) {
+    let pixels = on_grid(maze);
+    let mut report = SolveReport::new(trace);
+
+    let (path, filled) = match ends(&pixels, start, end, maze) {
+        Some((start, end)) => deadend::dead_end_filling(&pixels, start, end, &mut report),
+        None => (None, Vec::new()),
     };
+    report.finish(&path);
+
+    let filled = (0..filled.len())
+        .filter(|&cell| filled[cell])
+        .map(|cell| node(&pixels, maze, cell))
+        .collect();
+
+    (
+        to_nodes(&pixels, path, maze),
+        filled,
+        to_pixels(&pixels, report),
+    )
+}
+
+// The maze with one cell for every pixel, leaving room for the wall round
+// the outside, and a link between every pair of passages side by side
+fn on_grid(maze: &HashSet<(u32, u32)>) -> GridMaze<SquareGrid> {
+    let columns = maze.iter().map(|&(x, _)| x + 2).max().unwrap_or(1);
+    let rows = maze.iter().map(|&(_, y)| y + 2).max().unwrap_or(1);
+    let mut pixels = GridMaze::new(SquareGrid::new(columns, rows));
+
+    for &(x, y) in maze {
+        let cell = pixels
+            .grid
+            .cell_at(x, y)
+            .expect("on_grid -- passage off the grid!");
+
+        for &next in &[(x + 1, y), (x, y + 1)] {
+            if maze.contains(&next) {
+                let other = pixels
+                    .grid
+                    .cell_at(next.0, next.1)
+                    .expect("on_grid -- passage off the grid!");
+                pixels.link(cell, other);
+            }
+        }
+    }
+
+    pixels
+}
+
+// The start and end cells, so long as both are passages
+fn ends(
+    pixels: &GridMaze<SquareGrid>,
+    start: (u32, u32),
+    end: (u32, u32),
+    maze: &HashSet<(u32, u32)>,
+) -> Option<(usize, usize)> {
+    if !maze.contains(&start) || !maze.contains(&end) {
+        return None;
+    }
+
+    Some((
+        pixels.grid.cell_at(start.0, start.1)?,
+        pixels.grid.cell_at(end.0, end.1)?,
+    ))
+}
+
+fn node<'a>(
+    pixels: &GridMaze<SquareGrid>,
+    maze: &'a HashSet<(u32, u32)>,
+    cell: usize,
+) -> &'a (u32, u32) {
+    maze.get(&pixels.grid.position(cell))
+        .expect("node -- cell is not a passage!")
+}
+
+// Paths from the grid solvers run from the start, where mazes of pixels have
+// always given them from the end back to the start
+fn to_nodes<'a>(
+    pixels: &GridMaze<SquareGrid>,
+    path: Option<Vec<usize>>,
+    maze: &'a HashSet<(u32, u32)>,
+) -> Option<Vec<&'a (u32, u32)>> {
+    path.map(|path| {
+        path.into_iter()
+            .rev()
+            .map(|cell| node(pixels, maze, cell))
+            .collect()
+    })
+}
 
+fn to_pixels(pixels: &GridMaze<SquareGrid>, report: SolveReport<usize>) -> SolveReport {
+    report.map(|cell| pixels.grid.position(cell))
+}
+
+// Follows the trail of where each cell was reached from back to the start,
+// giving the path from the start to the end
+fn walk_back(came_from: &HashMap<usize, usize>, end: usize) -> Vec<usize> {
+    let mut path = vec![end];
+    let mut cell = end;
+
+    while came_from[&cell] != cell {
+        cell = came_from[&cell];
+        path.push(cell);
+    }
+
+    path.reverse();
     path
 }
+
+/// Passages next to the node. Nodes on the very edge of the maze have no
+/// neighbours beyond it.
+pub fn neighbours(node: (u32, u32), maze: &HashSet<(u32, u32)>) -> Vec<&(u32, u32)> {
+    let (x, y) = node;
+
+    let positions = [
+        y.checked_sub(1).map(|y| (x, y)),
+        x.checked_add(1).map(|x| (x, y)),
+        y.checked_add(1).map(|y| (x, y)),
+        x.checked_sub(1).map(|x| (x, y)),
+    ];
+
+    positions
+        .iter()
+        .filter_map(|position| position.and_then(|position| maze.get(&position)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grids::{HexGrid, WeaveGrid};
+    use testing::{check_every_grid, maze_from_rows, GridCheck};
+
+    fn is_connected_path(path: &[&(u32, u32)]) -> bool {
+        path.windows(2).all(|pair| {
+            let x = (pair[0].0 as i32 - pair[1].0 as i32).abs();
+            let y = (pair[0].1 as i32 - pair[1].1 as i32).abs();
+
+            x + y == 1
+        })
+    }
+
+    #[test]
+    fn test_pledge_around_island() {
+        let maze = maze_from_rows(&[
+            "#########",
+            "#.......#",
+            "#.#####.#",
+            "#.#####.#",
+            "#.......#",
+            "#########",
+        ]);
+
+        let path = solve_maze(Solver::Pledge, (1, 1), (7, 4), &maze, false)
+            .0
+            .unwrap();
+
+        assert_eq!(**path.first().unwrap(), (7, 4));
+        assert_eq!(**path.last().unwrap(), (1, 1));
+        assert!(is_connected_path(&path));
+    }
+
+    #[test]
+    fn test_pledge_unreachable() {
+        let maze = maze_from_rows(&["#######", "#...#.#", "#.#.#.#", "#...#.#", "#######"]);
+
+        assert!(solve_maze(Solver::Pledge, (1, 1), (5, 3), &maze, false)
+            .0
+            .is_none());
+    }
+
+    #[test]
+    fn test_left_turn_unreachable() {
+        let maze = maze_from_rows(&["#######", "#...#.#", "#.#.#.#", "#...#.#", "#######"]);
+
+        // Walking round the island comes back to the start, as does a
+        // start with nowhere to go
+        assert!(solve_maze(Solver::LeftTurn, (1, 1), (5, 3), &maze, false)
+            .0
+            .is_none());
+        assert!(solve_maze(Solver::LeftTurn, (5, 1), (1, 1), &maze, false)
+            .0
+            .is_none());
+
+        let single = maze_from_rows(&["###", "#.#", "###"]);
+        assert!(solve_maze(Solver::LeftTurn, (1, 1), (2, 1), &single, false)
+            .0
+            .is_none());
+    }
+
+    #[test]
+    fn test_tremaux_braided() {
+        let maze = maze_from_rows(&[
+            "#########",
+            "#.......#",
+            "#.#.###.#",
+            "#.......#",
+            "#.###.#.#",
+            "#.......#",
+            "#########",
+        ]);
+
+        let (path, marks, _) = solve_tremaux((1, 1), (7, 5), &maze, false);
+        let path = path.unwrap();
+
+        assert_eq!(**path.first().unwrap(), (7, 5));
+        assert_eq!(**path.last().unwrap(), (1, 1));
+        assert!(is_connected_path(&path));
+        assert!(marks.values().all(|mark| *mark == 1 || *mark == 2));
+        assert_eq!(marks[&(7, 5)], 1);
+    }
+
+    #[test]
+    fn test_tremaux_unreachable() {
+        let maze = maze_from_rows(&["#######", "#...#.#", "#.#.#.#", "#...#.#", "#######"]);
+
+        let (path, marks, _) = solve_tremaux((1, 1), (5, 3), &maze, false);
+
+        assert!(path.is_none());
+        assert_eq!(marks.len(), 8);
+        assert!(marks.values().all(|mark| *mark == 2));
+    }
+
+    #[test]
+    fn test_dead_end_filling_perfect() {
+        use mazebuilder::{generate_maze, Generator};
+
+        let maze = generate_maze(
+            Generator::DFS {
+                height: 31,
+                width: 31,
+            },
+            false,
+        );
+
+        let (path, filled, _) = solve_dead_end_filling((1, 1), (29, 29), &maze, false);
+        let path = path.unwrap();
+
+        // A perfect maze only has one solution, and everything else is filled
+        assert_eq!(
+            path,
+            solve_maze(Solver::BFS, (1, 1), (29, 29), &maze, false)
+                .0
+                .unwrap()
+        );
+        assert_eq!(path.len() + filled.len(), maze.len());
+    }
+
+    #[test]
+    fn test_dead_end_filling_braided() {
+        let maze = maze_from_rows(&[
+            "#########",
+            "#.......#",
+            "#.#.#####",
+            "#.....#.#",
+            "#####...#",
+            "#########",
+        ]);
+
+        let (path, filled, _) = solve_dead_end_filling((1, 1), (7, 3), &maze, false);
+        let path = path.unwrap();
+
+        assert_eq!(**path.first().unwrap(), (7, 3));
+        assert_eq!(**path.last().unwrap(), (1, 1));
+        assert!(is_connected_path(&path));
+
+        // Both routes around the loop are left open
+        let mut expected = vec![(4, 1), (5, 1), (6, 1), (7, 1)];
+        expected.sort();
+        let mut filled: Vec<(u32, u32)> = filled.into_iter().cloned().collect();
+        filled.sort();
+        assert_eq!(filled, expected);
+    }
+
+    #[test]
+    fn test_solve_reports() {
+        use mazebuilder::{generate_maze, Generator};
+
+        let maze = generate_maze(
+            Generator::DFS {
+                height: 21,
+                width: 21,
+            },
+            false,
+        );
+
+        let (bfs_path, bfs) = solve_maze(Solver::BFS, (1, 1), (19, 19), &maze, true);
+        let (_, astar) = solve_maze(Solver::AStar, (1, 1), (19, 19), &maze, false);
+        let (_, left) = solve_maze(Solver::LeftTurn, (1, 1), (19, 19), &maze, true);
+
+        // Searches never look at the same node twice and give the shortest path
+        assert_eq!(bfs.path_length, bfs_path.unwrap().len());
+        assert_eq!(bfs.revisits, 0);
+        assert_eq!(bfs.trace.unwrap().len(), bfs.expanded);
+        assert_eq!(astar.path_length, bfs.path_length);
+        assert!(astar.trace.is_none());
+
+        // Wall followers only ever hold one node, and walk back over old ground
+        assert_eq!(left.max_frontier, 1);
+        assert_eq!(left.path_length, left.expanded);
+        assert_eq!(left.expanded, left.trace.unwrap().len());
+        assert!(left.path_length >= bfs.path_length);
+    }
+
+    fn assert_walkable<G: Grid>(maze: &GridMaze<G>, path: &[usize], start: usize, end: usize) {
+        assert_eq!(path[0], start);
+        assert_eq!(path[path.len() - 1], end);
+
+        for pair in path.windows(2) {
+            assert!(maze.is_linked(pair[0], pair[1]), "walked through a wall");
+        }
+    }
+
+    // Every solver finds its way through a perfect maze on the grid. The
+    // Pledge solver only walks by compass directions across one level of
+    // squares, so on other grids it may find nothing.
+    fn assert_every_solver<G: Grid>(maze: &GridMaze<G>, compass: bool) {
+        let (start, end) = (maze.grid.start(), maze.grid.end());
+
+        let (shortest, _) = solve_grid(Solver::BFS, maze, start, end, false);
+        let shortest = shortest.expect("bfs found no path");
+
+        for &solver in Solver::ALL.iter() {
+            let (path, report) = solve_grid(solver, maze, start, end, false);
+            let path = match (path, solver) {
+                (Some(path), _) => path,
+                (None, Solver::Pledge) if !compass => continue,
+                (None, _) => panic!("{} found no path", solver.name()),
+            };
+
+            assert_walkable(maze, &path, start, end);
+            assert_eq!(report.path_length, path.len());
+
+            // A perfect maze has only one way through, wall followers may
+            // wander down dead ends on the way
+            match solver {
+                Solver::LeftTurn | Solver::Pledge => assert!(path.len() >= shortest.len()),
+                _ => assert_eq!(path, shortest, "{} took a detour", solver.name()),
+            }
+        }
+    }
+
+    struct EverySolver;
+
+    impl GridCheck for EverySolver {
+        fn check<G: Grid>(&mut self, name: &str, maze: &GridMaze<G>) {
+            assert_every_solver(maze, name == "square");
+        }
+    }
+
+    #[test]
+    fn test_every_solver_on_every_grid() {
+        check_every_grid(&mut EverySolver);
+    }
+
+    #[test]
+    fn test_tunnel_is_separate_from_bridge() {
+        // A passage down the middle of a three by three grid, with a tunnel
+        // under it from left to right
+        let mut maze = GridMaze::new(WeaveGrid::new(3, 3));
+        maze.link(1, 4);
+        maze.link(4, 7);
+        maze.link(3, 5);
+
+        // Going under skips the bridge, and the bridge can't turn into the
+        // tunnel. The Pledge solver stays on the surface, so never goes under.
+        for &solver in Solver::ALL.iter() {
+            let (path, _) = solve_grid(solver, &maze, 3, 5, false);
+            match solver {
+                Solver::Pledge => assert_eq!(path, None, "pledge went under"),
+                _ => assert_eq!(path, Some(vec![3, 5]), "{} didn't go under", solver.name()),
+            }
+
+            let (path, _) = solve_grid(solver, &maze, 1, 5, false);
+            assert_eq!(path, None, "{} turned off the bridge", solver.name());
+        }
+    }
+
+    #[test]
+    fn test_no_way_through() {
+        // Only the first two cells are joined
+        let mut maze = GridMaze::new(HexGrid::new(3, 3));
+        maze.link(0, 1);
+
+        for &solver in Solver::ALL.iter() {
+            let (path, report) = solve_grid(solver, &maze, 0, 8, false);
+            assert!(path.is_none(), "{} walked through a wall", solver.name());
+            assert_eq!(report.path_length, 0);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use grids::{Direction, Grid, GridMaze};
+
+use super::SolveReport;
+
+// location tracking object, for grids laid out in compass directions
+struct Person {
+    cell: usize,
+    facing: Direction,
+}
+
+// The heading the person tries to keep whenever they are not following a wall
+const PREFERRED: Direction = Direction::South;
+
+// Entry function. The person walks by compass directions across a single
+// level of squares, so grids laid out in other shapes have no way on at all,
+// and tunnels and stairs are never taken.
+pub fn pledge<G: Grid>(
+    maze: &GridMaze<G>,
+    start: usize,
+    end: usize,
+    report: &mut SolveReport<usize>,
+) -> Option<Vec<usize>> {
+    // Create a person at the start of the maze facing the preferred direction
+    let mut person = Person {
+        cell: start,
+        facing: PREFERRED,
+    };
+
+    // Sum of all turns made while following a wall in quarter turns, left is
+    // +1 and right is -1
+    let mut turns: i32 = 0;
+
+    // Where the person stood and which way they faced after their first step
+    // along the wall they are following
+    let mut first: Option<(usize, Direction)> = None;
+
+    let mut path = Vec::new();
+
+    loop {
+        // Add the current node to the path.
+        path.push(person.cell);
+
+        // Only ever looking at the one cell we are stood on
+        report.expand(&person.cell);
+        report.frontier(1);
+
+        // Check if we are at the end of the maze
+        if person.cell == end {
+            return Some(path);
+        }
+
+        match first {
+            None => {
+                // Keep walking in the preferred direction until we hit a wall
+                if let Some(forward) = get_direction(maze, &person, person.facing) {
+                    person.cell = forward;
+                    continue;
+                }
+
+                // Turn right so that the wall is on our left and start following it
+                let (next_step, new_facing, turn) = find_first_step(maze, &person)?;
+                turns += turn;
+                person = Person {
+                    cell: next_step,
+                    facing: new_facing,
+                };
+                first = Some((person.cell, person.facing));
+            }
+            Some(first) => {
+                let (next_step, new_facing, turn) = find_next_step(maze, &person);
+                turns += turn;
+                person = Person {
+                    cell: next_step,
+                    facing: new_facing,
+                };
+
+                // Back where we started along this wall facing the same way,
+                // the walk has gone all the way round it without the turns
+                // unwinding, and will only go round again
+                if turns != 0 && (person.cell, person.facing) == first {
+                    return None;
+                }
+            }
+        }
+
+        // Once all of the turns have unwound we are facing the preferred
+        // direction again and can leave the wall.
+        if turns == 0 {
+            first = None;
+        }
+    }
+}
+
+fn find_first_step<G: Grid>(
+    maze: &GridMaze<G>,
+    person: &Person,
+) -> Option<(usize, Direction, i32)> {
+    // The way forward is blocked, so keep turning right until there is a path.
+    if let Some((cell, dir)) = look_right(maze, person) {
+        return Some((cell, dir, -1));
+    };
+
+    if let Some((cell, dir)) = look_back(maze, person) {
+        return Some((cell, dir, -2));
+    };
+
+    look_left(maze, person).map(|(cell, dir)| (cell, dir, -3))
+}
+
+fn find_next_step<G: Grid>(maze: &GridMaze<G>, person: &Person) -> (usize, Direction, i32) {
+    // Same rules as the left turn solver, but keep a note of how far we turned.
+    if let Some((cell, dir)) = look_left(maze, person) {
+        return (cell, dir, 1);
+    };
+
+    if let Some(forward) = get_direction(maze, person, person.facing) {
+        return (forward, person.facing, 0);
+    };
+
+    if let Some((cell, dir)) = look_right(maze, person) {
+        return (cell, dir, -1);
+    };
+
+    let (cell, dir) = look_back(maze, person)
+        .expect("find_next_step -- No Path back from current location found!");
+
+    (cell, dir, -2)
+}
+
+// The open passage one step from the person in the given direction. Only
+// passages across a shared side count, as walking under bridges or up stairs
+// would leave the flat maze the turns are counted across.
+fn get_direction<G: Grid>(
+    maze: &GridMaze<G>,
+    person: &Person,
+    direction: Direction,
+) -> Option<usize> {
+    maze.grid
+        .step(person.cell, direction)
+        .filter(|&next| maze.is_linked(person.cell, next))
+}
+
+fn look_left<G: Grid>(maze: &GridMaze<G>, person: &Person) -> Option<(usize, Direction)> {
+    get_cell_from_direction(maze, person, turn(person.facing, 3))
+}
+
+fn look_right<G: Grid>(maze: &GridMaze<G>, person: &Person) -> Option<(usize, Direction)> {
+    get_cell_from_direction(maze, person, turn(person.facing, 1))
+}
+
+fn look_back<G: Grid>(maze: &GridMaze<G>, person: &Person) -> Option<(usize, Direction)> {
+    get_cell_from_direction(maze, person, turn(person.facing, 2))
+}
+
+fn get_cell_from_direction<G: Grid>(
+    maze: &GridMaze<G>,
+    person: &Person,
+    dir: Direction,
+) -> Option<(usize, Direction)> {
+    get_direction(maze, person, dir).map(|cell| (cell, dir))
+}
+
+// The direction some number of quarter turns clockwise from the one given.
+// People only ever face across a level, never up or down the stairs.
+fn turn(facing: Direction, quarters: usize) -> Direction {
+    let index = Direction::FLAT
+        .iter()
+        .position(|&direction| direction == facing)
+        .expect("turn -- facing up or down!");
+
+    Direction::FLAT[(index + quarters) % 4]
+}
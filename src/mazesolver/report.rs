@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+type Node = (u32, u32);
+
+/// Statistics gathered while a solver works its way through a maze. Nodes are
+/// maze pixels, or cell numbers for mazes on other grids.
+#[derive(Serialize, Deserialize)]
+pub struct SolveReport<N = Node> {
+    /// Number of times a node was expanded (or stepped on, for wall followers)
+    pub expanded: usize,
+    /// Largest number of nodes waiting to be looked at any one time
+    pub max_frontier: usize,
+    /// Number of nodes in the returned path, zero if no path was found
+    pub path_length: usize,
+    /// Number of expansions of a node that had already been expanded
+    pub revisits: usize,
+    /// Every expansion in the order they happened, if it was asked for
+    #[serde(skip)]
+    pub trace: Option<Vec<N>>,
+    #[serde(skip)]
+    seen: HashSet<N>,
+}
+
+impl<N: Copy + Eq + Hash> SolveReport<N> {
+    pub fn new(trace: bool) -> SolveReport<N> {
+        SolveReport {
+            expanded: 0,
+            max_frontier: 0,
+            path_length: 0,
+            revisits: 0,
+            trace: if trace { Some(Vec::new()) } else { None },
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Records that the solver has expanded the given node.
+    pub fn expand(&mut self, node: &N) {
+        self.expanded += 1;
+
+        if !self.seen.insert(*node) {
+            self.revisits += 1;
+        }
+
+        if let Some(ref mut trace) = self.trace {
+            trace.push(*node);
+        }
+    }
+
+    /// Records the path the solver settled on.
+    pub fn finish<T>(&mut self, path: &Option<Vec<T>>) {
+        self.path_length = path.as_ref().map_or(0, |path| path.len());
+    }
+
+    /// The same report with every node swapped for another, such as a cell
+    /// number for the maze pixel it stands for.
+    pub fn map<M: Copy + Eq + Hash, F: Fn(N) -> M>(self, f: F) -> SolveReport<M> {
+        SolveReport {
+            expanded: self.expanded,
+            max_frontier: self.max_frontier,
+            path_length: self.path_length,
+            revisits: self.revisits,
+            trace: self.trace.map(|trace| trace.into_iter().map(&f).collect()),
+            seen: self.seen.into_iter().map(&f).collect(),
+        }
+    }
+
+    /// Records the current size of the solver's frontier.
+    pub fn frontier(&mut self, size: usize) {
+        if size > self.max_frontier {
+            self.max_frontier = size;
+        }
+    }
+}
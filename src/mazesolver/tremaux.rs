@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use grids::{pair, Grid, GridMaze};
+
+use super::SolveReport;
+
+// How many times each passage was walked, keyed smallest cell first
+pub type PassageMarks = HashMap<(usize, usize), u8>;
+
+pub fn tremaux<G: Grid>(
+    maze: &GridMaze<G>,
+    start: usize,
+    end: usize,
+    report: &mut SolveReport<usize>,
+) -> (Option<Vec<usize>>, PassageMarks) {
+    // Number of times each passage has been walked, never more than twice
+    let mut marks: HashMap<(usize, usize), u8> = HashMap::new();
+    let mark = |marks: &HashMap<(usize, usize), u8>, a: usize, b: usize| {
+        marks.get(&pair(a, b)).cloned().unwrap_or(0)
+    };
+
+    let mut current = start;
+    let mut previous: Option<usize> = None;
+
+    // Cells joined by passages that have only been marked once, which is the
+    // route back to the start
+    let mut path = vec![start];
+
+    loop {
+        report.expand(&current);
+        report.frontier(path.len());
+
+        if current == end {
+            return (Some(path), marks);
+        }
+
+        let passages = maze.passages(current);
+
+        // If we walked down a new passage into somewhere we have already
+        // been, turn around and go back the way we came
+        let turn_back = previous.filter(|&entrance| {
+            mark(&marks, current, entrance) == 1
+                && passages
+                    .iter()
+                    .any(|&cell| cell != entrance && mark(&marks, current, cell) > 0)
+        });
+
+        // Otherwise take the passage with the fewest marks, never one marked
+        // twice. If every passage has been marked twice there is no way to
+        // the end.
+        let fewest = passages
+            .iter()
+            .cloned()
+            .filter(|&cell| mark(&marks, current, cell) < 2)
+            .min_by_key(|&cell| mark(&marks, current, cell));
+        let next = match turn_back.or(fewest) {
+            Some(next) => next,
+            None => return (None, marks),
+        };
+
+        let count = marks.entry(pair(current, next)).or_insert(0);
+        *count += 1;
+
+        if *count == 1 {
+            path.push(next);
+        } else {
+            path.pop();
+        }
+
+        previous = Some(current);
+        current = next;
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+type Node = (u32, u32);
+
+// A point where wall lines can meet, one per grid corner
+type Corner = (u32, u32);
+
+/// Settings for drawing a maze as an SVG.
+pub struct SvgOptions {
+    /// Width and height of each cell, in SVG units
+    pub cell_size: f32,
+    /// Thickness of the wall lines
+    pub stroke_width: f32,
+    /// Thickness of the solution line
+    pub path_width: f32,
+    pub wall_colour: String,
+    /// Fill behind the maze, left transparent if there isn't one
+    pub background: Option<String>,
+    pub path_colour: String,
+}
+
+impl SvgOptions {
+    pub fn new() -> SvgOptions {
+        SvgOptions {
+            cell_size: 10f32,
+            stroke_width: 2f32,
+            path_width: 3f32,
+            wall_colour: String::from("#000000"),
+            background: Some(String::from("#ffffff")),
+            path_colour: String::from("#d02020"),
+        }
+    }
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions::new()
+    }
+}
+
+pub fn generate_svg(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<Node>,
+    path: Option<&[&Node]>,
+    options: &SvgOptions,
+    filename: &str,
+) -> io::Result<()> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+
+    write_svg(&mut writer, height, width, node_map, path, options)
+}
+
+pub fn write_svg<W: Write>(
+    w: &mut W,
+    height: u32,
+    width: u32,
+    node_map: &HashSet<Node>,
+    path: Option<&[&Node]>,
+    options: &SvgOptions,
+) -> io::Result<()> {
+    let cell = options.cell_size;
+    let full_width = (width / 2) as f32 * cell;
+    let full_height = (height / 2) as f32 * cell;
+
+    // Leave room for the outer walls, which are centred on the edge
+    let pad = options.stroke_width / 2f32;
+
+    writeln!(
+        w,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        full_width + 2f32 * pad,
+        full_height + 2f32 * pad,
+        -pad,
+        -pad,
+        full_width + 2f32 * pad,
+        full_height + 2f32 * pad
+    )?;
+
+    if let Some(ref background) = options.background {
+        writeln!(
+            w,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            -pad,
+            -pad,
+            full_width + 2f32 * pad,
+            full_height + 2f32 * pad,
+            escape(background)
+        )?;
+    }
+
+    writeln!(
+        w,
+        r#"<g fill="none" stroke="{}" stroke-width="{}" stroke-linecap="square">"#,
+        escape(&options.wall_colour),
+        options.stroke_width
+    )?;
+    for line in wall_lines(height, width, node_map) {
+        let points: Vec<String> = line
+            .iter()
+            .map(|&(x, y)| format!("{},{}", x as f32 * cell, y as f32 * cell))
+            .collect();
+        writeln!(w, r#"<polyline points="{}"/>"#, points.join(" "))?;
+    }
+    writeln!(w, "</g>")?;
+
+    // Maze pixels sit at half cell steps, cells on the odd ones and the gaps
+    // between them on the even ones, so halving lines them up with the walls.
+    if let Some(path) = path {
+        let points = straighten(path.iter().map(|node| **node).collect());
+        let steps: Vec<String> = points
+            .iter()
+            .enumerate()
+            .map(|(index, &(x, y))| {
+                let command = if index == 0 { "M" } else { "L" };
+                format!(
+                    "{}{},{}",
+                    command,
+                    x as f32 * cell / 2f32,
+                    y as f32 * cell / 2f32
+                )
+            })
+            .collect();
+
+        if !steps.is_empty() {
+            writeln!(
+                w,
+                r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                steps.join(" "),
+                escape(&options.path_colour),
+                options.path_width
+            )?;
+        }
+    }
+
+    writeln!(w, "</svg>")
+}
+
+/// Makes text safe to put inside a quoted attribute.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+// Joins up every wall in the maze into as few lines as it can
+fn wall_lines(height: u32, width: u32, node_map: &HashSet<Node>) -> Vec<Vec<Corner>> {
+    let mut walls: BTreeMap<Corner, BTreeSet<Corner>> = BTreeMap::new();
+
+    {
+        let mut add_wall = |a: Corner, b: Corner| {
+            walls.entry(a).or_default().insert(b);
+            walls.entry(b).or_default().insert(a);
+        };
+
+        // Walls between cells on the same row run up and down, walls between
+        // cells on the same column run across. Corner posts are covered by
+        // the walls either side of them.
+        for y in 0..height {
+            for x in 0..width {
+                if node_map.contains(&(x, y)) {
+                    continue;
+                }
+
+                if x % 2 == 0 && y % 2 == 1 {
+                    add_wall((x / 2, y / 2), (x / 2, y / 2 + 1));
+                } else if x % 2 == 1 && y % 2 == 0 {
+                    add_wall((x / 2, y / 2), (x / 2 + 1, y / 2));
+                }
+            }
+        }
+    }
+
+    // Corners with an odd number of walls, where lines have to end
+    let mut loose_ends: BTreeSet<Corner> = walls
+        .iter()
+        .filter(|&(_, joined)| joined.len() % 2 == 1)
+        .map(|(corner, _)| *corner)
+        .collect();
+
+    let mut lines = Vec::new();
+
+    loop {
+        // Starting from a loose end means a line doesn't get split in the middle
+        let start = loose_ends
+            .iter()
+            .next()
+            .or_else(|| walls.keys().next())
+            .cloned();
+
+        let start = match start {
+            Some(corner) => corner,
+            None => break,
+        };
+
+        let mut line = vec![start];
+        let mut current = start;
+
+        // Keep heading the same way if possible, otherwise take any turning
+        while let Some(next) = next_corner(&walls, &line, current) {
+            remove_wall(&mut walls, &mut loose_ends, current, next);
+            remove_wall(&mut walls, &mut loose_ends, next, current);
+
+            line.push(next);
+            current = next;
+        }
+
+        lines.push(straighten(line));
+    }
+
+    lines
+}
+
+// Takes one end of a wall off a corner, dropping the corner once it has no
+// walls left so that only corners with walls still to draw are kept
+fn remove_wall(
+    walls: &mut BTreeMap<Corner, BTreeSet<Corner>>,
+    loose_ends: &mut BTreeSet<Corner>,
+    corner: Corner,
+    other: Corner,
+) {
+    let joined = walls.get_mut(&corner).unwrap();
+    joined.remove(&other);
+
+    if joined.len() % 2 == 1 {
+        loose_ends.insert(corner);
+    } else {
+        loose_ends.remove(&corner);
+    }
+
+    if joined.is_empty() {
+        walls.remove(&corner);
+    }
+}
+
+fn next_corner(
+    walls: &BTreeMap<Corner, BTreeSet<Corner>>,
+    line: &[Corner],
+    current: Corner,
+) -> Option<Corner> {
+    let joined = walls.get(&current)?;
+
+    if line.len() > 1 {
+        let previous = line[line.len() - 2];
+        let ahead = (
+            (2 * current.0).wrapping_sub(previous.0),
+            (2 * current.1).wrapping_sub(previous.1),
+        );
+
+        if joined.contains(&ahead) {
+            return Some(ahead);
+        }
+    }
+
+    joined.iter().next().cloned()
+}
+
+// Drops any point that sits in the middle of a straight run
+fn straighten(points: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    let mut straight: Vec<(u32, u32)> = Vec::with_capacity(points.len());
+
+    for point in points {
+        if straight.len() > 1 {
+            let a = straight[straight.len() - 2];
+            let b = straight[straight.len() - 1];
+
+            let same_column = a.0 == b.0 && b.0 == point.0;
+            let same_row = a.1 == b.1 && b.1 == point.1;
+
+            if same_column || same_row {
+                straight.pop();
+            }
+        }
+
+        straight.push(point);
+    }
+
+    straight
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::maze_from_rows;
+
+    #[test]
+    fn test_single_cell_is_one_closed_line() {
+        let maze = maze_from_rows(&["###", "#.#", "###"]);
+
+        let lines = wall_lines(3, 3, &maze);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].len(), 5);
+        assert_eq!(lines[0].first(), lines[0].last());
+    }
+
+    #[test]
+    fn test_walls_are_merged() {
+        let maze = maze_from_rows(&["#######", "#.....#", "#####.#", "#.....#", "#######"]);
+
+        // The outer wall and the wall sticking in from the left, which joins it
+        let lines = wall_lines(5, 7, &maze);
+        let segments: usize = lines
+            .iter()
+            .map(|line| {
+                line.windows(2)
+                    .map(|pair| {
+                        let dx = (pair[0].0 as i64 - pair[1].0 as i64).abs();
+                        let dy = (pair[0].1 as i64 - pair[1].1 as i64).abs();
+                        (dx + dy) as usize
+                    })
+                    .sum::<usize>()
+            })
+            .sum();
+
+        assert_eq!(segments, 3 + 2 + 3 + 2 + 2);
+        assert!(lines.len() <= 2);
+    }
+
+    #[test]
+    fn test_svg_output() {
+        let maze = maze_from_rows(&["#####", "#...#", "###.#", "#...#", "#####"]);
+        let path: Vec<&(u32, u32)> = [(1, 3), (2, 3), (3, 3), (3, 2), (3, 1), (2, 1), (1, 1)]
+            .iter()
+            .collect();
+
+        let mut bytes = Vec::new();
+        write_svg(&mut bytes, 5, 5, &maze, Some(&path), &SvgOptions::new()).unwrap();
+        let svg = String::from_utf8(bytes).unwrap();
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(r#"<path d="M5,15 L15,15 L15,5 L5,5""#));
+    }
+
+    #[test]
+    fn test_colours_are_escaped() {
+        let maze = maze_from_rows(&["###", "#.#", "###"]);
+
+        let mut options = SvgOptions::new();
+        options.wall_colour = String::from(r#"red" onload="alert(1)"#);
+        options.background = Some(String::from("<black>&"));
+
+        let mut bytes = Vec::new();
+        write_svg(&mut bytes, 3, 3, &maze, None, &options).unwrap();
+        let svg = String::from_utf8(bytes).unwrap();
+
+        assert!(svg.contains(r#"stroke="red&quot; onload=&quot;alert(1)""#));
+        assert!(svg.contains(r#"fill="&lt;black&gt;&amp;""#));
+    }
+}
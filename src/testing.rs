@@ -0,0 +1,62 @@
+// Helpers shared by the tests of several modules
+
+use std::collections::HashSet;
+
+use grids::{Grid, GridMaze, HexGrid, LevelGrid, PolarGrid, SquareGrid, TriangleGrid, WeaveGrid};
+use mazebuilder::{generate_grid, Algorithm};
+use rand::{SeedableRng, StdRng};
+
+/// Builds a maze from rows of '#' walls and '.' paths.
+pub fn maze_from_rows(rows: &[&str]) -> HashSet<(u32, u32)> {
+    let mut maze = HashSet::new();
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            if c == '.' {
+                maze.insert((x as u32, y as u32));
+            }
+        }
+    }
+
+    maze
+}
+
+/// A check run over mazes on every kind of grid, see `check_every_grid`.
+pub trait GridCheck {
+    fn check<G: Grid>(&mut self, name: &str, maze: &GridMaze<G>);
+}
+
+/// Generates a perfect maze with each algorithm on every kind of grid, and
+/// runs the check over each of them.
+pub fn check_every_grid<C: GridCheck>(check: &mut C) {
+    let mut rng = StdRng::from_seed(&[7usize][..]);
+
+    for &algorithm in &[Algorithm::Dfs, Algorithm::Kruskal] {
+        let rng = &mut rng;
+
+        check.check(
+            "square",
+            &generate_grid(SquareGrid::new(15, 11), algorithm, 0.0, rng),
+        );
+        check.check(
+            "hex",
+            &generate_grid(HexGrid::new(12, 9), algorithm, 0.0, rng),
+        );
+        check.check(
+            "polar",
+            &generate_grid(PolarGrid::new(7), algorithm, 0.0, rng),
+        );
+        check.check(
+            "triangle",
+            &generate_grid(TriangleGrid::new(17, 9), algorithm, 0.0, rng),
+        );
+        check.check(
+            "levels",
+            &generate_grid(LevelGrid::new(8, 6, 4), algorithm, 0.0, rng),
+        );
+        check.check(
+            "weave",
+            &generate_grid(WeaveGrid::new(14, 11), algorithm, 0.0, rng),
+        );
+    }
+}
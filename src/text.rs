@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet};
+
+type Node = (u32, u32);
+
+/// Characters used to print a maze as text.
+pub enum TextStyle {
+    /// `#` walls with the solution marked by `.`
+    Ascii,
+    /// Box drawing walls with the solution marked by arrows
+    Unicode,
+}
+
+pub fn render_text(
+    height: u32,
+    width: u32,
+    node_map: &HashSet<Node>,
+    path: Option<&[&Node]>,
+    style: &TextStyle,
+) -> String {
+    let start = (1, 1);
+    let end = (width - 2, height - 2);
+
+    // Paths run from the end back to the start, so each node's step towards
+    // the end is the node before it
+    let mut steps: HashMap<Node, Node> = HashMap::new();
+    if let Some(path) = path {
+        for pair in path.windows(2) {
+            steps.insert(*pair[1], *pair[0]);
+        }
+    }
+
+    let mut text = String::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            let position = (x, y);
+
+            let c = if !node_map.contains(&position) {
+                match *style {
+                    TextStyle::Ascii => '#',
+                    TextStyle::Unicode => wall_char(position, height, width, node_map),
+                }
+            } else if position == start {
+                'S'
+            } else if position == end {
+                'E'
+            } else {
+                match steps.get(&position) {
+                    Some(next) => match *style {
+                        TextStyle::Ascii => '.',
+                        TextStyle::Unicode => arrow(position, *next),
+                    },
+                    None => ' ',
+                }
+            };
+
+            text.push(c);
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+// Picks the box drawing character that joins up with the walls around it
+fn wall_char(position: Node, height: u32, width: u32, node_map: &HashSet<Node>) -> char {
+    let (x, y) = position;
+    let is_wall = |x: u32, y: u32| x < width && y < height && !node_map.contains(&(x, y));
+
+    let north = y > 0 && is_wall(x, y - 1);
+    let east = is_wall(x + 1, y);
+    let south = is_wall(x, y + 1);
+    let west = x > 0 && is_wall(x - 1, y);
+
+    match (north, east, south, west) {
+        (false, false, false, false) => '▪',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╶',
+        (true, true, false, false) => '└',
+        (false, false, true, false) => '╷',
+        (true, false, true, false) => '│',
+        (false, true, true, false) => '┌',
+        (true, true, true, false) => '├',
+        (false, false, false, true) => '╴',
+        (true, false, false, true) => '┘',
+        (false, true, false, true) => '─',
+        (true, true, false, true) => '┴',
+        (false, false, true, true) => '┐',
+        (true, false, true, true) => '┤',
+        (false, true, true, true) => '┬',
+        (true, true, true, true) => '┼',
+    }
+}
+
+fn arrow(from: Node, to: Node) -> char {
+    if to.0 > from.0 {
+        '→'
+    } else if to.0 < from.0 {
+        '←'
+    } else if to.1 > from.1 {
+        '↓'
+    } else {
+        '↑'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::maze_from_rows;
+
+    fn test_maze() -> HashSet<(u32, u32)> {
+        maze_from_rows(&["#####", "#...#", "###.#", "#...#", "#####"])
+    }
+
+    // Runs from the end back to the start, along the right hand side
+    const PATH: [(u32, u32); 5] = [(3, 3), (3, 2), (3, 1), (2, 1), (1, 1)];
+
+    #[test]
+    fn test_ascii_render() {
+        let maze = test_maze();
+        let path: Vec<&(u32, u32)> = PATH.iter().collect();
+
+        let text = render_text(5, 5, &maze, Some(&path), &TextStyle::Ascii);
+        assert_eq!(text, "#####\n#S..#\n###.#\n#  E#\n#####\n");
+
+        let text = render_text(5, 5, &maze, None, &TextStyle::Ascii);
+        assert_eq!(text, "#####\n#S  #\n### #\n#  E#\n#####\n");
+    }
+
+    #[test]
+    fn test_unicode_render() {
+        let maze = test_maze();
+        let path: Vec<&(u32, u32)> = PATH.iter().collect();
+
+        let text = render_text(5, 5, &maze, Some(&path), &TextStyle::Unicode);
+        assert_eq!(text, "┌───┐\n│S→↓│\n├─╴↓│\n│  E│\n└───┘\n");
+    }
+}